@@ -0,0 +1,362 @@
+use std::{convert::Infallible, path::PathBuf, str::FromStr, time::Duration};
+
+use rand::Rng;
+use tetris::{
+    lock_delay::LockResetMode, piece_set::PieceSetError, Board, PieceSet, RotationSystem, Settings,
+};
+use thiserror::Error;
+
+/// The narrowest board the tetrominoes can spawn on. Other piece sets and NRS can need more, which
+/// is checked once the pieces are known
+const MIN_BOARD_WIDTH: usize = 4;
+/// The fewest rows every piece can spawn and fall in, not counting any garbage
+const MIN_BOARD_HEIGHT: usize = 4;
+/// Gravity stops getting faster after this level
+const MAX_STARTING_LEVEL: usize = 20;
+/// The number of moves a block gets with `--lock-reset move`
+const MOVE_RESET_LIMIT: u32 = 15;
+/// The most garbage rows a cheese race can be played to
+const MAX_CHEESE_LINES: usize = 1000;
+/// The most people that can play at once, one for each set of keys
+const MAX_PLAYERS: usize = 2;
+/// The most bots a battle can be fought against, so every board still fits on screen
+const MAX_BOTS: usize = 3;
+/// The most frames online inputs can be held back for, a sixth of a second
+const MAX_INPUT_DELAY: usize = 10;
+
+#[derive(Debug, Error)]
+pub enum ArgsError {
+    #[error("missing value for `{0}`")]
+    MissingValue(String),
+    #[error("invalid value `{value}` for `{flag}`")]
+    InvalidValue { flag: String, value: String },
+    #[error("`{flag}` should be between {min} and {max}")]
+    OutOfRange {
+        flag: String,
+        min: usize,
+        max: usize,
+    },
+    #[error("unknown argument `{0}`")]
+    UnknownArgument(String),
+    #[error("failed to load `{path}`: {source}")]
+    PieceSet {
+        path: PathBuf,
+        source: PieceSetError,
+    },
+    #[error("the pieces don't fit on a board {0} columns wide")]
+    BoardTooNarrow(usize),
+    #[error("a board {0} rows tall is too short to play on")]
+    BoardTooShort(usize),
+    #[error("`--rotation` can't be used with `--pieces`, which sets its own rotations")]
+    RotationWithPieces,
+    #[error("an online game is for one player on each machine")]
+    TooManyPlayersOnline,
+}
+
+/// How to reach the other player in an online game
+pub enum Network {
+    /// Wait for them to connect on this port
+    Host(u16),
+    /// Connect to them at this address
+    Connect(String),
+}
+
+/// A local socket spectators can watch a game through
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Endpoint {
+    /// A TCP address, like `127.0.0.1:4000`
+    Tcp(String),
+    /// The path to a Unix socket. Any address with a `/` in it is taken as a path
+    #[cfg(unix)]
+    Unix(PathBuf),
+}
+
+impl FromStr for Endpoint {
+    type Err = Infallible;
+
+    fn from_str(addr: &str) -> Result<Self, Self::Err> {
+        #[cfg(unix)]
+        if addr.contains('/') {
+            return Ok(Self::Unix(PathBuf::from(addr)));
+        }
+        Ok(Self::Tcp(addr.to_string()))
+    }
+}
+
+/// Options passed to the binary on the command line
+pub struct Args {
+    /// The seed for the piece sequence. Picked at random if `--seed` isn't passed
+    pub seed: u64,
+    pub settings: Settings,
+    pub network: Option<Network>,
+    /// Where to let spectators watch the game from
+    pub spectate: Option<Endpoint>,
+    /// The game to watch instead of playing
+    pub watch: Option<Endpoint>,
+}
+
+impl Args {
+    pub fn parse(mut args: impl Iterator<Item = String>) -> Result<Self, ArgsError> {
+        let mut seed = None;
+        let mut settings = Settings::default();
+        let mut rotation_system = None;
+        let mut piece_set_path = None;
+        let mut network = None;
+        let mut spectate = None;
+        let mut watch = None;
+
+        while let Some(arg) = args.next() {
+            match arg.as_str() {
+                "--seed" => seed = Some(parse_value(&arg, args.next())?),
+                "--width" => {
+                    settings.board_width = parse_value(&arg, args.next())?;
+                    check_range(
+                        &arg,
+                        settings.board_width,
+                        MIN_BOARD_WIDTH,
+                        Board::MAX_WIDTH,
+                    )?;
+                }
+                "--height" => {
+                    settings.board_height = parse_value(&arg, args.next())?;
+                    check_range(
+                        &arg,
+                        settings.board_height,
+                        MIN_BOARD_HEIGHT,
+                        usize::from(u8::MAX),
+                    )?;
+                }
+                "--level" => {
+                    settings.starting_level = parse_value(&arg, args.next())?;
+                    check_range(
+                        &arg,
+                        settings.starting_level as usize,
+                        1,
+                        MAX_STARTING_LEVEL,
+                    )?;
+                }
+                "--rotation" => {
+                    let value = next_value(&arg, args.next())?;
+                    rotation_system = Some(match value.as_str() {
+                        "srs" => RotationSystem::Srs,
+                        "srs+" => RotationSystem::SrsPlus,
+                        "ars" => RotationSystem::Ars,
+                        "nrs" => RotationSystem::Nrs,
+                        _ => return Err(invalid_value(&arg, value)),
+                    });
+                }
+                "--mode" => settings.mode = parse_value(&arg, args.next())?,
+                "--cheese-lines" => {
+                    settings.cheese_lines = parse_value(&arg, args.next())?;
+                    check_range(&arg, settings.cheese_lines as usize, 1, MAX_CHEESE_LINES)?;
+                }
+                "--holes" => settings.hole_placement = parse_value(&arg, args.next())?,
+                "--big" => settings.big = true,
+                "--stack" => settings.stack_effect = parse_value(&arg, args.next())?,
+                "--randomizer" => settings.randomizer = parse_value(&arg, args.next())?,
+                "--host" => network = Some(Network::Host(parse_value(&arg, args.next())?)),
+                "--connect" => network = Some(Network::Connect(next_value(&arg, args.next())?)),
+                "--spectate" => spectate = Some(parse_value(&arg, args.next())?),
+                "--watch" => watch = Some(parse_value(&arg, args.next())?),
+                "--pieces" => piece_set_path = Some(PathBuf::from(next_value(&arg, args.next())?)),
+                _ if parse_handling_flag(&mut settings, &arg, &mut args)? => (),
+                _ if parse_versus_flag(&mut settings, &arg, &mut args)? => (),
+                _ => return Err(ArgsError::UnknownArgument(arg)),
+            }
+        }
+
+        settings.piece_set = match (piece_set_path, rotation_system) {
+            // Piece set files come with rotations and kicks of their own
+            (Some(_), Some(_)) => return Err(ArgsError::RotationWithPieces),
+            (Some(path), None) => {
+                PieceSet::load(&path).map_err(|source| ArgsError::PieceSet { path, source })?
+            }
+            (None, rotation_system) => PieceSet::tetrominoes(rotation_system.unwrap_or_default()),
+        };
+        if !settings.piece_set.fits_board(settings.field_width()) {
+            return Err(ArgsError::BoardTooNarrow(settings.board_width));
+        }
+        // Big mode halves the rows pieces fall through, and garbage the board starts with takes up
+        // more. Pieces also need a row below where they spawn to fall into
+        let spawn_rows = settings.piece_set.spawn_area_bottom(settings.field_width()) as usize + 1;
+        let field_height = settings.field_height();
+        let min_height = MIN_BOARD_HEIGHT.max(spawn_rows + 1);
+        if field_height < min_height + settings.mode.cheese_rows(field_height) {
+            return Err(ArgsError::BoardTooShort(settings.board_height));
+        }
+
+        if network.is_some() {
+            if settings.players > 1 {
+                return Err(ArgsError::TooManyPlayersOnline);
+            }
+            // Online battles are fought against the other player instead of bots
+            settings.bots = 0;
+        }
+
+        Ok(Self {
+            seed: seed.unwrap_or_else(random_seed),
+            settings,
+            network,
+            spectate,
+            watch,
+        })
+    }
+}
+
+/// Parse one of the flags that tune how pieces are controlled and locked. Returns false if `flag`
+/// isn't one of them
+fn parse_handling_flag(
+    settings: &mut Settings,
+    flag: &str,
+    args: &mut impl Iterator<Item = String>,
+) -> Result<bool, ArgsError> {
+    match flag {
+        "--lock-delay" => {
+            let millis = parse_value(flag, args.next())?;
+            settings.lock_delay.delay = Duration::from_millis(millis);
+        }
+        "--lock-reset" => {
+            let value = next_value(flag, args.next())?;
+            settings.lock_delay.reset_mode = match value.as_str() {
+                "infinite" => LockResetMode::Infinite,
+                "move" => LockResetMode::MoveReset {
+                    max_resets: MOVE_RESET_LIMIT,
+                },
+                "step" => LockResetMode::StepReset,
+                "classic" => LockResetMode::Classic,
+                _ => return Err(invalid_value(flag, value)),
+            };
+        }
+        "--no-lowest-row-rule" => settings.lock_delay.lowest_row_refills_resets = false,
+        "--das" => {
+            settings.handling.das = Duration::from_millis(parse_value(flag, args.next())?);
+        }
+        "--arr" => {
+            settings.handling.arr = Duration::from_millis(parse_value(flag, args.next())?);
+        }
+        "--sdf" => {
+            let value = next_value(flag, args.next())?;
+            settings.handling.soft_drop_factor = match value.parse() {
+                Ok(factor) if factor >= 1.0 => factor,
+                _ => return Err(invalid_value(flag, value)),
+            };
+        }
+        _ => return Ok(false),
+    }
+
+    Ok(true)
+}
+
+/// Parse one of the flags for playing against other boards. Returns false if `flag` isn't one of
+/// them
+fn parse_versus_flag(
+    settings: &mut Settings,
+    flag: &str,
+    args: &mut impl Iterator<Item = String>,
+) -> Result<bool, ArgsError> {
+    match flag {
+        "--players" => {
+            settings.players = parse_value(flag, args.next())?;
+            check_range(flag, settings.players, 1, MAX_PLAYERS)?;
+        }
+        "--shared-seed" => settings.shared_seed = true,
+        "--bots" => {
+            settings.bots = parse_value(flag, args.next())?;
+            check_range(flag, settings.bots, 1, MAX_BOTS)?;
+        }
+        "--bot-delay" => {
+            settings.bot_move_delay = Duration::from_millis(parse_value(flag, args.next())?);
+        }
+        "--input-delay" => {
+            settings.input_delay = parse_value(flag, args.next())?;
+            check_range(flag, settings.input_delay as usize, 0, MAX_INPUT_DELAY)?;
+        }
+        _ => return Ok(false),
+    }
+
+    Ok(true)
+}
+
+/// Generate a seed that is short enough to read off the HUD and type back in
+pub fn random_seed() -> u64 {
+    rand::thread_rng().gen_range(0..=u64::from(u32::MAX))
+}
+
+fn parse_value<T: FromStr>(flag: &str, value: Option<String>) -> Result<T, ArgsError> {
+    let value = next_value(flag, value)?;
+    value.parse().map_err(|_| invalid_value(flag, value))
+}
+
+fn next_value(flag: &str, value: Option<String>) -> Result<String, ArgsError> {
+    value.ok_or_else(|| ArgsError::MissingValue(flag.to_string()))
+}
+
+fn invalid_value(flag: &str, value: String) -> ArgsError {
+    ArgsError::InvalidValue {
+        flag: flag.to_string(),
+        value,
+    }
+}
+
+fn check_range(flag: &str, value: usize, min: usize, max: usize) -> Result<(), ArgsError> {
+    if (min..=max).contains(&value) {
+        Ok(())
+    } else {
+        Err(ArgsError::OutOfRange {
+            flag: flag.to_string(),
+            min,
+            max,
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use tetris::level;
+
+    use super::*;
+
+    fn parse(args: &str) -> Result<Args, ArgsError> {
+        Args::parse(args.split_whitespace().map(String::from))
+    }
+
+    #[test]
+    fn boards_too_short_to_play_on_are_rejected() {
+        for (args, height) in [
+            ("--big --height 6", 6),
+            ("--mode cheese --height 6", 6),
+            ("--mode cheese --big --height 4", 4),
+            ("--mode cheese --big --height 12", 12),
+        ] {
+            assert!(
+                matches!(parse(args), Err(ArgsError::BoardTooShort(h)) if h == height),
+                "{args}"
+            );
+        }
+
+        for args in [
+            "--height 4",
+            "--big --height 8",
+            "--mode cheese --height 7",
+            "--mode cheese --big --height 14",
+        ] {
+            assert!(parse(args).is_ok(), "{args}");
+        }
+    }
+
+    #[test]
+    fn starting_levels_go_up_to_where_gravity_stops_getting_faster() {
+        for level in [0, MAX_STARTING_LEVEL + 1] {
+            let args = format!("--level {level}");
+            let Err(ArgsError::OutOfRange { max, .. }) = parse(&args) else {
+                panic!("{args} should be out of range");
+            };
+            assert_eq!(max, MAX_STARTING_LEVEL);
+        }
+
+        let args = parse(&format!("--level {MAX_STARTING_LEVEL}")).expect("the level should parse");
+        let gravity = level::gravity(args.settings.starting_level);
+        assert!((gravity - level::MAX_GRAVITY).abs() < f64::EPSILON);
+        assert!(level::gravity(args.settings.starting_level - 2) < level::MAX_GRAVITY);
+    }
+}
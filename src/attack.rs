@@ -0,0 +1,215 @@
+//! Garbage sent between boards in a battle
+//!
+//! Line clears send garbage according to the attack table, and garbage sent to a board waits in
+//! its queue until a piece locks without clearing anything. Clearing lines cancels queued garbage
+//! before any is sent back
+use std::collections::VecDeque;
+
+use crate::scoring::{LineClear, TSpin};
+
+/// Extra garbage for a difficult clear that continues a back-to-back chain
+pub const BACK_TO_BACK_ATTACK: u32 = 1;
+/// The garbage sent for clearing the whole board, on top of the clear itself
+pub const PERFECT_CLEAR_ATTACK: u32 = 10;
+/// Extra garbage for each combo count, capped at the last entry
+const COMBO_ATTACK: [u32; 11] = [0, 1, 1, 2, 2, 3, 3, 4, 4, 4, 5];
+/// The most garbage rows that rise after a single piece locks. The rest stay queued
+pub const GARBAGE_CAP: u32 = 8;
+
+/// The number of garbage lines `clear` sends. `is_back_to_back` is whether the clear continued a
+/// back-to-back chain, and `combo` is the combo it was part of
+#[must_use]
+pub fn attack(clear: LineClear, is_back_to_back: bool, combo: u32) -> u32 {
+    if clear.lines == 0 {
+        return 0;
+    }
+
+    let lines = u32::try_from(clear.lines).unwrap_or(u32::MAX);
+    let base = match clear.t_spin {
+        None => match lines {
+            1 => 0,
+            2 => 1,
+            3 => 2,
+            // Tetrises, and bigger clears by bigger pieces, send every line
+            _ => lines,
+        },
+        Some(TSpin::Mini) => lines - 1,
+        Some(TSpin::Full) => lines * 2,
+    };
+    let back_to_back = if is_back_to_back {
+        BACK_TO_BACK_ATTACK
+    } else {
+        0
+    };
+    let combo = COMBO_ATTACK[(combo as usize).min(COMBO_ATTACK.len() - 1)];
+    let perfect_clear = if clear.perfect_clear {
+        PERFECT_CLEAR_ATTACK
+    } else {
+        0
+    };
+
+    base + back_to_back + combo + perfect_clear
+}
+
+/// Garbage waiting to rise into a board, in the order it was received
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct GarbageQueue {
+    /// The number of lines in each attack. Every attack comes in with its own hole
+    attacks: VecDeque<u32>,
+}
+
+impl GarbageQueue {
+    #[must_use]
+    pub const fn new() -> Self {
+        Self {
+            attacks: VecDeque::new(),
+        }
+    }
+
+    /// Queue an attack of `lines` lines
+    pub fn push(&mut self, lines: u32) {
+        if lines > 0 {
+            self.attacks.push_back(lines);
+        }
+    }
+
+    /// The total number of lines waiting to rise
+    #[must_use]
+    pub fn total(&self) -> u32 {
+        self.attacks.iter().sum()
+    }
+
+    /// Cancel up to `lines` queued lines, oldest first. Returns the lines left over
+    pub fn cancel(&mut self, mut lines: u32) -> u32 {
+        while let Some(attack) = self.attacks.front_mut() {
+            if lines == 0 {
+                break;
+            }
+
+            let cancelled = lines.min(*attack);
+            *attack -= cancelled;
+            lines -= cancelled;
+            if *attack == 0 {
+                self.attacks.pop_front();
+            }
+        }
+
+        lines
+    }
+
+    /// Take the attacks that should rise after a piece locks, splitting the last one if they add
+    /// up to more than [`GARBAGE_CAP`]
+    pub fn take_rising(&mut self) -> Vec<u32> {
+        let mut rising = vec![];
+        let mut room = GARBAGE_CAP;
+        while let Some(attack) = self.attacks.front_mut() {
+            if room == 0 {
+                break;
+            }
+
+            let lines = room.min(*attack);
+            rising.push(lines);
+            room -= lines;
+            *attack -= lines;
+            if *attack == 0 {
+                self.attacks.pop_front();
+            }
+        }
+
+        rising
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const fn clear(lines: i64) -> LineClear {
+        LineClear {
+            lines,
+            t_spin: None,
+            perfect_clear: false,
+        }
+    }
+
+    const fn t_spin(t_spin: TSpin, lines: i64) -> LineClear {
+        LineClear {
+            t_spin: Some(t_spin),
+            ..clear(lines)
+        }
+    }
+
+    #[test]
+    fn clears_send_garbage_by_the_attack_table() {
+        let sent: Vec<u32> = (0..=5)
+            .map(|lines| attack(clear(lines), false, 0))
+            .collect();
+        assert_eq!(sent, [0, 0, 1, 2, 4, 5]);
+
+        assert_eq!(attack(t_spin(TSpin::Mini, 0), false, 0), 0);
+        assert_eq!(attack(t_spin(TSpin::Mini, 1), false, 0), 0);
+        assert_eq!(attack(t_spin(TSpin::Mini, 2), false, 0), 1);
+        assert_eq!(attack(t_spin(TSpin::Full, 0), false, 0), 0);
+        assert_eq!(attack(t_spin(TSpin::Full, 1), false, 0), 2);
+        assert_eq!(attack(t_spin(TSpin::Full, 2), false, 0), 4);
+        assert_eq!(attack(t_spin(TSpin::Full, 3), false, 0), 6);
+    }
+
+    #[test]
+    fn back_to_back_combos_and_perfect_clears_add_to_the_attack() {
+        assert_eq!(attack(clear(4), true, 0), 4 + BACK_TO_BACK_ATTACK);
+        assert_eq!(
+            attack(t_spin(TSpin::Full, 2), true, 0),
+            4 + BACK_TO_BACK_ATTACK
+        );
+
+        let combos: Vec<u32> = (0..12)
+            .map(|combo| attack(clear(1), false, combo))
+            .collect();
+        assert_eq!(combos, [0, 1, 1, 2, 2, 3, 3, 4, 4, 4, 5, 5]);
+
+        let perfect_clear = LineClear {
+            perfect_clear: true,
+            ..clear(2)
+        };
+        assert_eq!(attack(perfect_clear, false, 0), 1 + PERFECT_CLEAR_ATTACK);
+        assert_eq!(
+            attack(perfect_clear, true, 2),
+            1 + BACK_TO_BACK_ATTACK + 1 + PERFECT_CLEAR_ATTACK
+        );
+
+        // Nothing is sent without a line clear, whatever the chain
+        assert_eq!(attack(clear(0), true, 5), 0);
+    }
+
+    #[test]
+    fn clearing_lines_cancels_the_oldest_garbage_first() {
+        let mut queue = GarbageQueue::new();
+        queue.push(2);
+        queue.push(0);
+        queue.push(3);
+        assert_eq!(queue.total(), 5);
+
+        assert_eq!(queue.cancel(1), 0);
+        assert_eq!(queue.total(), 4);
+        assert_eq!(queue.cancel(2), 0);
+        assert_eq!(queue.take_rising(), [2]);
+
+        queue.push(1);
+        assert_eq!(queue.cancel(4), 3);
+        assert_eq!(queue.total(), 0);
+    }
+
+    #[test]
+    fn only_so_much_garbage_rises_after_each_piece() {
+        let mut queue = GarbageQueue::new();
+        queue.push(5);
+        queue.push(6);
+        queue.push(1);
+
+        assert_eq!(queue.take_rising(), [5, GARBAGE_CAP - 5]);
+        assert_eq!(queue.total(), 6 + 1 - (GARBAGE_CAP - 5));
+        assert_eq!(queue.take_rising(), [6 - (GARBAGE_CAP - 5), 1]);
+        assert_eq!(queue.take_rising(), Vec::<u32>::new());
+    }
+}
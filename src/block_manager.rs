@@ -0,0 +1,209 @@
+use std::{collections::VecDeque, sync::Arc};
+
+use gemini_engine::{containers::CanCollide, core::Vec2D};
+mod blocks;
+pub use blocks::{
+    block_manipulation as tetris_core, Block, BlockType, LastMove, RotationSystem, Tetromino, Turn,
+};
+use rand::{rngs::StdRng, SeedableRng};
+
+use crate::{
+    lock_delay::LockTimer,
+    randomizer::{Randomizer, RandomizerKind},
+    scoring::TSpin,
+    PieceSet, Settings,
+};
+
+pub struct BlockManager {
+    rng: StdRng,
+    randomizer: Box<dyn Randomizer>,
+    /// The upcoming pieces, starting with the next one to spawn
+    queue: VecDeque<Arc<BlockType>>,
+    pub block: Block,
+    pub ghost_block: Block,
+    pub held_piece: Option<Arc<BlockType>>,
+    pub has_held: bool,
+    pub lock_timer: LockTimer,
+    // Constants
+    piece_preview_count: usize,
+    board_width: usize,
+    piece_set: PieceSet,
+    randomizer_kind: RandomizerKind,
+    seed: u64,
+}
+
+impl BlockManager {
+    /// Create a new `BlockManager`. Every piece is drawn from an RNG seeded with `seed`, so two
+    /// managers created with the same seed and randomizer will produce the same sequence of pieces
+    #[must_use]
+    pub fn new(settings: &Settings, seed: u64) -> Self {
+        // Replaced as soon as the first piece spawns
+        let placeholder = Block::new(Arc::clone(&settings.piece_set.pieces()[0]));
+
+        let mut tmp = Self {
+            rng: StdRng::seed_from_u64(seed),
+            randomizer: settings.randomizer.build(&settings.piece_set),
+            queue: VecDeque::new(),
+            block: placeholder.clone(),
+            ghost_block: placeholder,
+            held_piece: None,
+            has_held: false,
+            lock_timer: LockTimer::new(settings.lock_delay),
+            piece_preview_count: settings.piece_preview_count,
+            board_width: settings.field_width(),
+            piece_set: settings.piece_set.clone(),
+            randomizer_kind: settings.randomizer,
+            seed,
+        };
+        tmp.generate_new_block();
+        tmp
+    }
+
+    /// The seed the piece sequence was generated from
+    #[must_use]
+    pub const fn seed(&self) -> u64 {
+        self.seed
+    }
+
+    /// The randomizer the piece sequence is dealt by
+    #[must_use]
+    pub const fn randomizer(&self) -> RandomizerKind {
+        self.randomizer_kind
+    }
+
+    /// Call when a block is placed
+    ///
+    /// Returns true if the block is placed without having fallen from the row it spawned in,
+    /// indicating a lost game
+    pub fn reset(&mut self) -> bool {
+        self.has_held = false;
+        self.block.pos.y <= self.block.shape.spawn_offset.y
+    }
+
+    pub fn generate_new_block(&mut self) {
+        // One more than the preview, so the queue is still full once the next piece spawns
+        while self.queue.len() <= self.piece_preview_count {
+            let index = self.randomizer.next_piece(&mut self.rng);
+            self.queue
+                .push_back(Arc::clone(&self.piece_set.pieces()[index]));
+        }
+        let next_piece = self.queue.pop_front().unwrap_or_else(|| unreachable!());
+
+        self.spawn(next_piece);
+    }
+
+    fn spawn(&mut self, piece: Arc<BlockType>) {
+        self.block = Block::spawn(piece, self.board_width);
+        self.lock_timer.restart(self.block.pos.y);
+    }
+
+    /// Attempt to move the block. Updates the lock timer and returns true if successful
+    pub fn try_move_block(&mut self, collision: &impl CanCollide, offset: Vec2D) -> bool {
+        let did_move = tetris_core::try_move_block(collision, &mut self.block, offset);
+        if did_move {
+            self.lock_timer.on_block_moved(self.block.pos.y);
+        }
+        did_move
+    }
+
+    /// Attempt to rotate the block. Updates the lock timer and returns true if successful
+    pub fn try_rotate_block(&mut self, collision: &impl CanCollide, turn: Turn) -> bool {
+        let did_rotate = tetris_core::try_rotate_block(collision, &mut self.block, turn);
+        if did_rotate {
+            self.lock_timer.on_block_moved(self.block.pos.y);
+        }
+        did_rotate
+    }
+
+    /// Move the block up a row to make way for garbage rising beneath it
+    pub const fn push_up(&mut self) {
+        self.block.pos.y -= 1;
+        self.lock_timer.on_pushed_up();
+    }
+
+    /// Hold the current block
+    pub fn hold(&mut self) {
+        if !self.has_held {
+            let current_held_piece = self.held_piece.take();
+            self.held_piece = Some(Arc::clone(&self.block.shape));
+            match current_held_piece {
+                Some(piece) => self.spawn(piece),
+                None => {
+                    self.generate_new_block();
+                }
+            }
+            self.has_held = true;
+        }
+    }
+
+    pub fn generate_ghost_block(&mut self, collision: &impl CanCollide) {
+        let mut ghost_block = self.block.clone();
+        ghost_block.is_ghost = true;
+
+        while tetris_core::try_move_block(collision, &mut ghost_block, Vec2D::new(0, 1)) {}
+
+        self.ghost_block = ghost_block;
+    }
+
+    /// Check whether the active block has been spun into a T-spin
+    pub fn t_spin(&self, collision: &impl CanCollide) -> Option<TSpin> {
+        tetris_core::detect_t_spin(collision, &self.block)
+    }
+
+    /// The upcoming pieces, starting with the next one to spawn
+    pub fn queue(&self) -> impl Iterator<Item = &Arc<BlockType>> {
+        self.queue.iter().take(self.piece_preview_count)
+    }
+
+    #[must_use]
+    pub const fn piece_set(&self) -> &PieceSet {
+        &self.piece_set
+    }
+}
+
+impl Clone for BlockManager {
+    fn clone(&self) -> Self {
+        // Cloning a block makes a solid copy of it, so the ghost has to be marked again
+        let mut ghost_block = self.ghost_block.clone();
+        ghost_block.is_ghost = self.ghost_block.is_ghost;
+
+        Self {
+            rng: self.rng.clone(),
+            randomizer: self.randomizer.clone(),
+            queue: self.queue.clone(),
+            block: self.block.clone(),
+            ghost_block,
+            held_piece: self.held_piece.clone(),
+            has_held: self.has_held,
+            lock_timer: self.lock_timer.clone(),
+            piece_preview_count: self.piece_preview_count,
+            board_width: self.board_width,
+            piece_set: self.piece_set.clone(),
+            randomizer_kind: self.randomizer_kind,
+            seed: self.seed,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::Board;
+
+    #[test]
+    fn copies_keep_the_ghost_and_deal_the_same_pieces() {
+        let settings = Settings::default();
+        let board = Board::new(settings.board_width, settings.board_height);
+        let mut manager = BlockManager::new(&settings, 5);
+        manager.generate_ghost_block(&board);
+
+        let mut copy = manager.clone();
+        assert!(copy.ghost_block.is_ghost);
+        assert_eq!(copy.ghost_block.pos, manager.ghost_block.pos);
+        for _ in 0..30 {
+            manager.generate_new_block();
+            copy.generate_new_block();
+            assert_eq!(copy.block.shape, manager.block.shape);
+        }
+    }
+}
@@ -0,0 +1,183 @@
+use gemini_engine::core::{CanDraw, ColChar, Colour, Vec2D};
+use std::{collections::HashMap, sync::Arc};
+
+mod block_data;
+use block_data::BlockData;
+pub mod block_manipulation;
+mod rotation_system;
+pub use rotation_system::RotationSystem;
+
+/// How far a block is rotated in one go
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Turn {
+    Clockwise,
+    Anticlockwise,
+    /// A 180 degree rotation
+    Half,
+}
+
+impl Turn {
+    /// The number of rotation states moved through, positive for clockwise
+    const fn steps(self) -> isize {
+        match self {
+            Self::Clockwise => 1,
+            Self::Anticlockwise => -1,
+            Self::Half => 2,
+        }
+    }
+}
+
+/// One of the seven standard pieces, used to build the default piece set
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Tetromino {
+    I,
+    J,
+    L,
+    O,
+    S,
+    T,
+    Z,
+}
+
+impl Tetromino {
+    pub const ALL_VARIANTS: [Self; 7] = [
+        Self::I,
+        Self::J,
+        Self::L,
+        Self::O,
+        Self::S,
+        Self::T,
+        Self::Z,
+    ];
+
+    #[must_use]
+    pub fn colour(self) -> Colour {
+        BlockData::from(self).colour
+    }
+}
+
+/// The definition of a piece: its cells in every rotation state, its colour, the wall kicks it
+/// tries when rotating and where it spawns
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct BlockType {
+    pub name: String,
+    pub colour: Colour,
+    /// The cells of every rotation state, relative to the block's position. Clockwise rotation
+    /// moves to the next state
+    pub rotation_states: Vec<Vec<Vec2D>>,
+    /// The offsets to try, in order, when rotating from one state to another. The piece can't
+    /// make a rotation that has no kicks
+    pub kicks: HashMap<(usize, usize), Vec<Vec2D>>,
+    /// The kick that upgrades a T-spin Mini to a full T-spin when a quarter turn uses it, if the
+    /// rotation system has one
+    pub t_spin_upgrade_kick: Option<usize>,
+    /// The rotation state the piece spawns in
+    pub spawn_rotation: usize,
+    /// Where the piece spawns, relative to the top centre of the board
+    pub spawn_offset: Vec2D,
+}
+
+impl BlockType {
+    const fn get_colour(&self) -> ColChar {
+        // ColChar::EMPTY.with_char('▒') // Colourless
+        ColChar::SOLID.with_colour(self.colour)
+    }
+}
+
+/// The last action that successfully moved a block, which decides whether it can be a T-spin
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum LastMove {
+    Spawn,
+    /// A shift or a drop
+    Movement,
+    /// A rotation, along with the index of the wall kick that was used
+    Rotation {
+        turn: Turn,
+        kick: usize,
+    },
+}
+
+#[derive(Debug)]
+pub struct Block {
+    pub pos: Vec2D,
+    pub shape: Arc<BlockType>,
+    pub rotation: usize,
+    pub last_move: LastMove,
+    pub(super) is_ghost: bool,
+}
+
+impl Block {
+    /// Create a block in its spawn rotation
+    #[must_use]
+    pub fn new(shape: Arc<BlockType>) -> Self {
+        Self {
+            pos: Vec2D::ZERO,
+            rotation: shape.spawn_rotation,
+            shape,
+            last_move: LastMove::Spawn,
+            is_ghost: false,
+        }
+    }
+
+    /// Create a block at its spawn position, centred at the top of a board `board_width` columns
+    /// wide
+    #[must_use]
+    pub fn spawn(shape: Arc<BlockType>, board_width: usize) -> Self {
+        Self {
+            pos: Vec2D::new((board_width as i64 + 1) / 2, 0) + shape.spawn_offset,
+            ..Self::new(shape)
+        }
+    }
+
+    fn rot_state_len(&self) -> isize {
+        self.shape.rotation_states.len() as isize
+    }
+    #[must_use]
+    pub fn get_rotation_indexes(&self, turn: Turn) -> (usize, usize) {
+        (
+            self.rotation,
+            (self.rotation as isize + turn.steps()).rem_euclid(self.rot_state_len()) as usize,
+        )
+    }
+    pub fn rotate(&mut self, turn: Turn) {
+        self.rotation =
+            (self.rotation as isize + turn.steps()).rem_euclid(self.rot_state_len()) as usize;
+    }
+
+    /// The position of every cell in the block
+    #[must_use]
+    pub fn cells(&self) -> Vec<Vec2D> {
+        let rotation_states = &self.shape.rotation_states;
+
+        rotation_states[self.rotation.rem_euclid(rotation_states.len())]
+            .iter()
+            .map(|p| *p + self.pos)
+            .collect()
+    }
+}
+
+impl Clone for Block {
+    fn clone(&self) -> Self {
+        Self {
+            pos: self.pos,
+            shape: Arc::clone(&self.shape),
+            rotation: self.rotation,
+            last_move: self.last_move,
+            is_ghost: false,
+        }
+    }
+}
+
+impl CanDraw for Block {
+    fn draw_to(&self, canvas: &mut impl gemini_engine::core::Canvas) {
+        let block_colour = if self.is_ghost {
+            ColChar::BACKGROUND
+        } else {
+            self.shape.get_colour()
+        };
+
+        self.cells()
+            .into_iter()
+            .for_each(|p| canvas.plot(p, block_colour));
+    }
+}
@@ -1,6 +1,6 @@
 use std::collections::HashMap;
 
-use super::BlockType;
+use super::Tetromino;
 use gemini_engine::core::{Colour, Vec2D};
 
 pub(super) struct BlockData {
@@ -22,9 +22,9 @@ impl BlockData {
     }
 
     #[allow(clippy::too_many_lines)]
-    fn get_wall_kick_data(block_shape: BlockType) -> HashMap<(usize, usize), Vec<Vec2D>> {
+    fn get_wall_kick_data(block_shape: Tetromino) -> HashMap<(usize, usize), Vec<Vec2D>> {
         match block_shape {
-            BlockType::J | BlockType::L | BlockType::T | BlockType::S | BlockType::Z => {
+            Tetromino::J | Tetromino::L | Tetromino::T | Tetromino::S | Tetromino::Z => {
                 HashMap::from([
                     (
                         (0, 1),
@@ -108,7 +108,7 @@ impl BlockData {
                     ),
                 ])
             }
-            BlockType::I => HashMap::from([
+            Tetromino::I => HashMap::from([
                 (
                     (0, 1),
                     vec![
@@ -190,16 +190,16 @@ impl BlockData {
                     ],
                 ),
             ]),
-            BlockType::O => HashMap::new(),
+            Tetromino::O => HashMap::new(),
         }
     }
 }
 
-impl From<BlockType> for BlockData {
+impl From<Tetromino> for BlockData {
     #[allow(clippy::too_many_lines)]
-    fn from(block_shape: BlockType) -> Self {
+    fn from(block_shape: Tetromino) -> Self {
         match block_shape {
-            BlockType::O => Self::new(
+            Tetromino::O => Self::new(
                 vec![vec![
                     Vec2D::new(0, 0),
                     Vec2D::new(1, 0),
@@ -209,7 +209,7 @@ impl From<BlockType> for BlockData {
                 Colour::rgb(255, 255, 0),
                 Self::get_wall_kick_data(block_shape),
             ),
-            BlockType::I => Self::new(
+            Tetromino::I => Self::new(
                 vec![
                     vec![
                         Vec2D::new(-1, 0),
@@ -239,7 +239,7 @@ impl From<BlockType> for BlockData {
                 Colour::rgb(0, 255, 255),
                 Self::get_wall_kick_data(block_shape),
             ),
-            BlockType::T => Self::new(
+            Tetromino::T => Self::new(
                 vec![
                     vec![
                         Vec2D::new(0, -1),
@@ -269,7 +269,7 @@ impl From<BlockType> for BlockData {
                 Colour::rgb(255, 0, 255),
                 Self::get_wall_kick_data(block_shape),
             ),
-            BlockType::S => Self::new(
+            Tetromino::S => Self::new(
                 vec![
                     vec![
                         Vec2D::new(-1, 0),
@@ -299,7 +299,7 @@ impl From<BlockType> for BlockData {
                 Colour::rgb(0, 255, 0),
                 Self::get_wall_kick_data(block_shape),
             ),
-            BlockType::Z => Self::new(
+            Tetromino::Z => Self::new(
                 vec![
                     vec![
                         Vec2D::new(-1, -1),
@@ -329,7 +329,7 @@ impl From<BlockType> for BlockData {
                 Colour::rgb(255, 0, 0),
                 Self::get_wall_kick_data(block_shape),
             ),
-            BlockType::L => Self::new(
+            Tetromino::L => Self::new(
                 vec![
                     vec![
                         Vec2D::new(-1, 0),
@@ -359,7 +359,7 @@ impl From<BlockType> for BlockData {
                 Colour::rgb(255, 165, 0),
                 Self::get_wall_kick_data(block_shape),
             ),
-            BlockType::J => Self::new(
+            Tetromino::J => Self::new(
                 vec![
                     vec![
                         Vec2D::new(-1, -1),
@@ -0,0 +1,221 @@
+use gemini_engine::{containers::CanCollide, core::Vec2D};
+
+use super::{Block, LastMove, Turn};
+use crate::scoring::TSpin;
+
+/// Returns true if the block would overlap the collision if it was moved by `offset`
+pub fn will_overlap(collision: &impl CanCollide, block: &Block, offset: Vec2D) -> bool {
+    block
+        .cells()
+        .into_iter()
+        .any(|cell| collision.collides_with_pos(cell + offset))
+}
+
+pub fn try_move_block(collision: &impl CanCollide, block: &mut Block, offset: Vec2D) -> bool {
+    let did_move = !will_overlap(collision, block, offset);
+    if did_move {
+        block.pos += offset;
+        block.last_move = LastMove::Movement;
+    }
+
+    did_move
+}
+
+pub fn try_rotate_block(collision: &impl CanCollide, block: &mut Block, turn: Turn) -> bool {
+    let (from, to) = block.get_rotation_indexes(turn);
+    if from == to {
+        return false;
+    }
+    let mut hypothetical_block = block.clone();
+    hypothetical_block.rotate(turn);
+
+    let mut did_move = false;
+    let kicks = block
+        .shape
+        .kicks
+        .get(&(from, to))
+        .cloned()
+        .unwrap_or_default();
+    for (kick, possible_offset) in kicks.into_iter().enumerate() {
+        hypothetical_block.pos = block.pos + possible_offset;
+        if !will_overlap(collision, &hypothetical_block, Vec2D::ZERO) {
+            did_move = true;
+            block.pos += possible_offset;
+            block.rotate(turn);
+            block.last_move = LastMove::Rotation { turn, kick };
+            break;
+        }
+    }
+
+    did_move
+}
+
+/// The wall kick that upgrades a T-spin Mini to a full T-spin in SRS and SRS+. It's the last kick of
+/// every SRS quarter turn, which moves the T two rows into a slot it couldn't otherwise reach
+pub const TST_KICK: usize = 4;
+
+/// Detect a T-spin with the 3-corner rule
+///
+/// The last thing the block did must be a rotation, and at least three of the four cells diagonal
+/// to its centre must be filled. It's a full T-spin if both front corners (either side of the
+/// direction the T points) are filled, or a Mini if only one of them is, unless a quarter turn used
+/// the piece's [`BlockType::t_spin_upgrade_kick`](super::BlockType::t_spin_upgrade_kick)
+pub fn detect_t_spin(collision: &impl CanCollide, block: &Block) -> Option<TSpin> {
+    let LastMove::Rotation { turn, kick } = block.last_move else {
+        return None;
+    };
+    // Only a tetromino named T can T-spin
+    if block.shape.name != "T" {
+        return None;
+    }
+
+    // The centre of the T is the cell next to all three others. Rotation systems disagree on where
+    // that is, so find it from the cells rather than the rotation
+    let cells = block.cells();
+    if cells.len() != 4 {
+        return None;
+    }
+    let is_adjacent = |a: Vec2D, b: Vec2D| (a - b).abs().to_array().iter().sum::<i64>() == 1;
+    let centre = *cells.iter().find(|&&cell| {
+        cells
+            .iter()
+            .filter(|&&other| is_adjacent(cell, other))
+            .count()
+            == 3
+    })?;
+    // The arms either side of the centre cancel out, leaving the one the T points towards
+    let facing: Vec2D = cells.iter().map(|&cell| cell - centre).sum();
+    let side = Vec2D::new(facing.y, facing.x);
+
+    let count_filled = |corners: [Vec2D; 2]| {
+        corners
+            .into_iter()
+            .filter(|&corner| collision.collides_with_pos(centre + corner))
+            .count()
+    };
+    let front = count_filled([facing + side, facing - side]);
+    let back = count_filled([side - facing, -side - facing]);
+
+    match (front, back) {
+        (2, 1..) => Some(TSpin::Full),
+        (1, 2) if Some(kick) == block.shape.t_spin_upgrade_kick && turn != Turn::Half => {
+            Some(TSpin::Full)
+        }
+        (1, 2) => Some(TSpin::Mini),
+        _ => None,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::Arc;
+
+    use gemini_engine::core::ColChar;
+
+    use super::*;
+    use crate::{BlockType, Board, PieceSet, RotationSystem};
+
+    fn block(name: &str, pos: Vec2D, rotation: usize, last_move: LastMove) -> Block {
+        let shape = PieceSet::tetrominoes(RotationSystem::Srs)
+            .pieces()
+            .iter()
+            .find(|piece| piece.name == name)
+            .map(Arc::clone)
+            .expect("every tetromino should be in the piece set");
+        Block {
+            pos,
+            rotation,
+            last_move,
+            ..Block::new(shape)
+        }
+    }
+
+    fn board(cells: impl IntoIterator<Item = (i64, i64)>) -> Board {
+        let mut board = Board::new(10, 20);
+        for (x, y) in cells {
+            board.fill(Vec2D::new(x, y), ColChar::SOLID);
+        }
+        board
+    }
+
+    const fn rotation(kick: usize) -> LastMove {
+        LastMove::Rotation {
+            turn: Turn::Clockwise,
+            kick,
+        }
+    }
+
+    #[test]
+    fn three_corners_with_both_front_ones_is_a_t_spin() {
+        // A T pointing down into a slot in the bottom two rows, with one corner filled above it
+        let board = board(
+            (1..=10)
+                .filter(|&x| x != 5)
+                .map(|x| (x, 19))
+                .chain((1..=10).filter(|x| !(4..=6).contains(x)).map(|x| (x, 18)))
+                .chain([(4, 17)]),
+        );
+        let t = block("T", Vec2D::new(5, 18), 2, rotation(0));
+        assert_eq!(detect_t_spin(&board, &t), Some(TSpin::Full));
+
+        // Only spins count, not pieces shifted or dropped into place
+        let t = block("T", Vec2D::new(5, 18), 2, LastMove::Movement);
+        assert_eq!(detect_t_spin(&board, &t), None);
+
+        let l = block("L", Vec2D::new(5, 18), 2, rotation(0));
+        assert_eq!(detect_t_spin(&board, &l), None);
+    }
+
+    #[test]
+    fn one_front_corner_is_a_mini_unless_it_took_the_tst_kick() {
+        // A T pointing right against the left wall, with a cell below its point
+        let board = board([(2, 19)]);
+        let t = block("T", Vec2D::new(1, 18), 1, rotation(1));
+        assert_eq!(detect_t_spin(&board, &t), Some(TSpin::Mini));
+
+        let t = block("T", Vec2D::new(1, 18), 1, rotation(TST_KICK));
+        assert_eq!(detect_t_spin(&board, &t), Some(TSpin::Full));
+
+        // The upgrade is only for quarter turns
+        let half_turn = LastMove::Rotation {
+            turn: Turn::Half,
+            kick: TST_KICK,
+        };
+        let t = block("T", Vec2D::new(1, 18), 1, half_turn);
+        assert_eq!(detect_t_spin(&board, &t), Some(TSpin::Mini));
+    }
+
+    #[test]
+    fn fewer_than_three_corners_is_not_a_t_spin() {
+        let board = board([(4, 19)]);
+        let t = block("T", Vec2D::new(5, 18), 2, rotation(0));
+        assert_eq!(detect_t_spin(&board, &t), None);
+    }
+
+    #[test]
+    fn only_pieces_with_an_upgrade_kick_upgrade_minis() {
+        let board = board([(2, 19)]);
+        let mut t = block("T", Vec2D::new(1, 18), 1, rotation(TST_KICK));
+        t.shape = Arc::new(BlockType {
+            t_spin_upgrade_kick: None,
+            ..(*t.shape).clone()
+        });
+        assert_eq!(detect_t_spin(&board, &t), Some(TSpin::Mini));
+
+        for (rotation_system, upgrade_kick) in [
+            (RotationSystem::Srs, Some(TST_KICK)),
+            (RotationSystem::SrsPlus, Some(TST_KICK)),
+            (RotationSystem::Ars, None),
+            (RotationSystem::Nrs, None),
+        ] {
+            for piece in PieceSet::tetrominoes(rotation_system).pieces() {
+                let expected = if piece.name == "T" {
+                    upgrade_kick
+                } else {
+                    None
+                };
+                assert_eq!(piece.t_spin_upgrade_kick, expected, "{rotation_system:?}");
+            }
+        }
+    }
+}
@@ -0,0 +1,321 @@
+use gemini_engine::core::Vec2D;
+
+use super::{block_data::BlockData, block_manipulation::TST_KICK, Tetromino};
+
+/// ARS tries rotating in place, then one column to the right, then one to the left
+const ARS_KICKS: [Vec2D; 3] = [Vec2D::ZERO, Vec2D::new(1, 0), Vec2D::new(-1, 0)];
+
+/// The rules for how pieces rotate: the cells of each rotation state, the state pieces spawn in
+/// and the wall kicks tried when a rotation is blocked
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum RotationSystem {
+    /// The Super Rotation System used by modern guideline games
+    #[default]
+    Srs,
+    /// SRS with symmetric I piece kicks, as used by TETR.IO
+    SrsPlus,
+    /// The Arika Rotation System from the Tetris: The Grand Master series. Pieces sit at the
+    /// bottom of their bounding box and kick one column either way, but the I piece never kicks.
+    /// The centre column rule isn't applied
+    Ars,
+    /// The Nintendo Rotation System from the NES and Game Boy games. I, S and Z only have two
+    /// states, and nothing kicks
+    Nrs,
+}
+
+impl RotationSystem {
+    /// The cells of every rotation state of `piece`, relative to its position. Clockwise rotation
+    /// moves to the next state
+    #[must_use]
+    pub fn rotation_states(self, piece: Tetromino) -> Vec<Vec<Vec2D>> {
+        match self {
+            Self::Srs | Self::SrsPlus => BlockData::from(piece).rotation_states,
+            Self::Ars => ars_rotation_states(piece),
+            Self::Nrs => nrs_rotation_states(piece),
+        }
+    }
+
+    /// The rotation state `piece` spawns in
+    #[must_use]
+    pub const fn spawn_rotation(self, piece: Tetromino) -> usize {
+        match (self, piece) {
+            // NRS and ARS pieces spawn flat side up
+            (Self::Ars | Self::Nrs, Tetromino::J | Tetromino::L | Tetromino::T) => 2,
+            _ => 0,
+        }
+    }
+
+    /// The kick that upgrades a T-spin Mini made by a quarter turn of `piece` to a full T-spin, if
+    /// the rotation system has one. Only SRS and SRS+ have a fifth kick to upgrade with
+    #[must_use]
+    pub const fn t_spin_upgrade_kick(self, piece: Tetromino) -> Option<usize> {
+        match (self, piece) {
+            (Self::Srs | Self::SrsPlus, Tetromino::T) => Some(TST_KICK),
+            _ => None,
+        }
+    }
+
+    /// The offsets to try, in order, when rotating `piece` from state `from` to state `to`. Empty
+    /// if the piece can't make that rotation at all
+    #[must_use]
+    pub fn wall_kicks(self, piece: Tetromino, from: usize, to: usize) -> Vec<Vec2D> {
+        if from == to {
+            return vec![];
+        }
+        if from.abs_diff(to) == 2 {
+            return self.half_turn_kicks(piece, from, to);
+        }
+
+        match (self, piece) {
+            (Self::SrsPlus, Tetromino::I) => srs_plus_i_kicks(from, to),
+            (Self::Srs | Self::SrsPlus, _) => BlockData::from(piece)
+                .wall_kick_data
+                .remove(&(from, to))
+                .unwrap_or_default(),
+            (Self::Ars | Self::Nrs, Tetromino::O) => vec![],
+            (Self::Ars, Tetromino::I) | (Self::Nrs, _) => vec![Vec2D::ZERO],
+            (Self::Ars, _) => ARS_KICKS.to_vec(),
+        }
+    }
+
+    /// Kicks for 180 degree rotations. Only SRS+ has a kick table of its own, ARS reuses its
+    /// regular kicks and the rest don't kick at all
+    fn half_turn_kicks(self, piece: Tetromino, from: usize, to: usize) -> Vec<Vec2D> {
+        match (self, piece) {
+            (Self::SrsPlus, _) => srs_plus_half_turn_kicks(from, to),
+            (Self::Ars, Tetromino::I) | (Self::Srs | Self::Nrs, _) => vec![Vec2D::ZERO],
+            (Self::Ars, _) => ARS_KICKS.to_vec(),
+        }
+    }
+}
+
+fn srs_plus_half_turn_kicks(from: usize, to: usize) -> Vec<Vec2D> {
+    let kicks = match (from, to) {
+        (0, 2) => [(0, -1), (1, -1), (-1, -1), (1, 0), (-1, 0)],
+        (1, 3) => [(1, 0), (1, -2), (1, -1), (0, -2), (0, -1)],
+        (2, 0) => [(0, 1), (-1, 1), (1, 1), (-1, 0), (1, 0)],
+        (3, 1) => [(-1, 0), (-1, -2), (-1, -1), (0, -2), (0, -1)],
+        _ => return vec![],
+    };
+
+    std::iter::once(Vec2D::ZERO)
+        .chain(kicks.map(|(x, y)| Vec2D::new(x, y)))
+        .collect()
+}
+
+fn srs_plus_i_kicks(from: usize, to: usize) -> Vec<Vec2D> {
+    let kicks = match (from, to) {
+        (0, 1) => [(1, 0), (-2, 0), (-2, 1), (1, -2)],
+        (1, 0) => [(-1, 0), (2, 0), (-1, 2), (2, -1)],
+        (1, 2) => [(-1, 0), (2, 0), (-1, -2), (2, 1)],
+        (2, 1) => [(-2, 0), (1, 0), (-2, -1), (1, 2)],
+        (2, 3) => [(2, 0), (-1, 0), (2, -1), (-1, 2)],
+        (3, 2) => [(1, 0), (-2, 0), (1, -2), (-2, 1)],
+        (3, 0) => [(1, 0), (-2, 0), (1, 2), (-2, -1)],
+        (0, 3) => [(-1, 0), (2, 0), (2, 1), (-1, -2)],
+        _ => return vec![],
+    };
+
+    std::iter::once(Vec2D::ZERO)
+        .chain(kicks.map(|(x, y)| Vec2D::new(x, y)))
+        .collect()
+}
+
+/// Build rotation states from lists of `(x, y)` offsets
+fn states<const N: usize>(states: [[(i64, i64); 4]; N]) -> Vec<Vec<Vec2D>> {
+    states
+        .into_iter()
+        .map(|cells| cells.map(|(x, y)| Vec2D::new(x, y)).to_vec())
+        .collect()
+}
+
+fn ars_rotation_states(piece: Tetromino) -> Vec<Vec<Vec2D>> {
+    match piece {
+        Tetromino::I => states([
+            [(-1, 0), (0, 0), (1, 0), (2, 0)],
+            [(1, -1), (1, 0), (1, 1), (1, 2)],
+        ]),
+        Tetromino::J => states([
+            [(-1, 0), (-1, 1), (0, 1), (1, 1)],
+            [(0, -1), (1, -1), (0, 0), (0, 1)],
+            [(-1, 0), (0, 0), (1, 0), (1, 1)],
+            [(0, -1), (0, 0), (-1, 1), (0, 1)],
+        ]),
+        Tetromino::L => states([
+            [(1, 0), (-1, 1), (0, 1), (1, 1)],
+            [(0, -1), (0, 0), (0, 1), (1, 1)],
+            [(-1, 0), (0, 0), (1, 0), (-1, 1)],
+            [(-1, -1), (0, -1), (0, 0), (0, 1)],
+        ]),
+        Tetromino::O => states([[(0, 0), (1, 0), (0, -1), (1, -1)]]),
+        Tetromino::S => states([
+            [(0, 0), (1, 0), (-1, 1), (0, 1)],
+            [(-1, -1), (-1, 0), (0, 0), (0, 1)],
+        ]),
+        Tetromino::T => states([
+            [(0, 0), (-1, 1), (0, 1), (1, 1)],
+            [(0, -1), (0, 0), (1, 0), (0, 1)],
+            [(-1, 0), (0, 0), (1, 0), (0, 1)],
+            [(0, -1), (-1, 0), (0, 0), (0, 1)],
+        ]),
+        Tetromino::Z => states([
+            [(-1, 0), (0, 0), (0, 1), (1, 1)],
+            [(1, -1), (0, 0), (1, 0), (0, 1)],
+        ]),
+    }
+}
+
+fn nrs_rotation_states(piece: Tetromino) -> Vec<Vec<Vec2D>> {
+    match piece {
+        Tetromino::I => states([
+            [(-2, 0), (-1, 0), (0, 0), (1, 0)],
+            [(0, -2), (0, -1), (0, 0), (0, 1)],
+        ]),
+        Tetromino::S => states([
+            [(0, 0), (1, 0), (-1, 1), (0, 1)],
+            [(0, -1), (0, 0), (1, 0), (1, 1)],
+        ]),
+        Tetromino::Z => states([
+            [(-1, 0), (0, 0), (0, 1), (1, 1)],
+            [(1, -1), (0, 0), (1, 0), (0, 1)],
+        ]),
+        // The rest rotate about their centre, just like in SRS
+        Tetromino::J | Tetromino::L | Tetromino::O | Tetromino::T => {
+            BlockData::from(piece).rotation_states
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::Arc;
+
+    use super::*;
+    use crate::{
+        block_manager::{tetris_core::try_rotate_block, Block, Turn},
+        Board, PieceSet,
+    };
+
+    /// The rotation state that mirrors `state` left to right
+    const fn mirrored(state: usize) -> usize {
+        (4 - state) % 4
+    }
+
+    /// Every quarter turn between the four rotation states
+    fn quarter_turns() -> impl Iterator<Item = (usize, usize)> {
+        (0..4).flat_map(|from| [(from, (from + 1) % 4), (from, (from + 3) % 4)])
+    }
+
+    fn negated(kicks: Vec<Vec2D>) -> Vec<Vec2D> {
+        kicks.into_iter().map(|kick| -kick).collect()
+    }
+
+    #[test]
+    fn srs_kicks_turning_back_are_the_opposite_of_turning_there() {
+        for piece in Tetromino::ALL_VARIANTS {
+            for (from, to) in quarter_turns() {
+                assert_eq!(
+                    RotationSystem::Srs.wall_kicks(piece, to, from),
+                    negated(RotationSystem::Srs.wall_kicks(piece, from, to)),
+                    "{piece:?} {from} -> {to}"
+                );
+            }
+        }
+    }
+
+    #[test]
+    fn every_system_tries_turning_in_place_first() {
+        let systems = [
+            RotationSystem::Srs,
+            RotationSystem::SrsPlus,
+            RotationSystem::Ars,
+            RotationSystem::Nrs,
+        ];
+        for system in systems {
+            for piece in Tetromino::ALL_VARIANTS {
+                let states = system.rotation_states(piece).len();
+                for (from, to) in quarter_turns().filter(|&(from, to)| from.max(to) < states) {
+                    let kicks = system.wall_kicks(piece, from, to);
+                    assert!(
+                        kicks.is_empty() || kicks[0] == Vec2D::ZERO,
+                        "{system:?} {piece:?} {from} -> {to}"
+                    );
+                    if system == RotationSystem::Ars {
+                        assert!(kicks.iter().all(|kick| kick.y == 0 && kick.x.abs() <= 1));
+                    }
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn srs_plus_i_kicks_are_left_right_mirrors() {
+        for from in 0..4 {
+            for to in [(from + 1) % 4, (from + 3) % 4] {
+                let mirrored_kicks: Vec<Vec2D> = srs_plus_i_kicks(from, to)
+                    .into_iter()
+                    .map(|kick| Vec2D::new(-kick.x, kick.y))
+                    .collect();
+                assert_eq!(
+                    srs_plus_i_kicks(mirrored(from), mirrored(to)),
+                    mirrored_kicks,
+                    "{from} -> {to}"
+                );
+            }
+        }
+    }
+
+    #[test]
+    fn srs_plus_half_turn_kicks_are_symmetric() {
+        let mirrored_kicks: Vec<Vec2D> = srs_plus_half_turn_kicks(1, 3)
+            .into_iter()
+            .map(|kick| Vec2D::new(-kick.x, kick.y))
+            .collect();
+        assert_eq!(srs_plus_half_turn_kicks(3, 1), mirrored_kicks);
+        // Turning a flat piece over kicks up, and turning it back kicks down
+        assert_eq!(
+            srs_plus_half_turn_kicks(2, 0),
+            negated(srs_plus_half_turn_kicks(0, 2))
+        );
+    }
+
+    #[test]
+    fn only_srs_plus_and_ars_kick_half_turns() {
+        for (from, to) in [(0, 2), (1, 3), (2, 0), (3, 1)] {
+            for piece in Tetromino::ALL_VARIANTS {
+                let kicks = |system: RotationSystem| system.wall_kicks(piece, from, to);
+                assert_eq!(kicks(RotationSystem::Srs), [Vec2D::ZERO]);
+                assert_eq!(kicks(RotationSystem::Nrs), [Vec2D::ZERO]);
+                assert_eq!(kicks(RotationSystem::SrsPlus).len(), 6);
+                if piece != Tetromino::I {
+                    assert_eq!(kicks(RotationSystem::Ars), ARS_KICKS);
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn srs_plus_kicks_a_half_turn_off_the_floor() {
+        let t_on_the_floor = |system| {
+            let shape = PieceSet::tetrominoes(system)
+                .pieces()
+                .iter()
+                .find(|piece| piece.name == "T")
+                .map(Arc::clone)
+                .expect("every tetromino should be in the piece set");
+            Block {
+                pos: Vec2D::new(5, 19),
+                ..Block::new(shape)
+            }
+        };
+        let board = Board::new(10, 20);
+
+        // Pointing down, the T would poke through the floor without a kick
+        let mut t = t_on_the_floor(RotationSystem::Srs);
+        assert!(!try_rotate_block(&board, &mut t, Turn::Half));
+
+        let mut t = t_on_the_floor(RotationSystem::SrsPlus);
+        assert!(try_rotate_block(&board, &mut t, Turn::Half));
+        assert_eq!((t.pos, t.rotation), (Vec2D::new(5, 18), 2));
+    }
+}
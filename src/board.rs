@@ -0,0 +1,301 @@
+use gemini_engine::{
+    containers::CanCollide,
+    core::{CanDraw, Canvas, ColChar, Vec2D},
+};
+
+use crate::garbage::GARBAGE_COLOUR;
+
+/// A single row of the board. Bit `i` of `mask` is set when column `i` is filled, in which case
+/// `cells[i]` holds the colour it was filled with and `placed_at[i]` the frame it was filled on
+#[derive(Debug, Clone, PartialEq, Eq)]
+struct Row {
+    mask: u64,
+    cells: Vec<ColChar>,
+    placed_at: Vec<u64>,
+    /// Whether the row was pushed in as garbage rather than built from placed pieces
+    is_garbage: bool,
+}
+
+impl Row {
+    fn new(width: usize) -> Self {
+        Self {
+            mask: 0,
+            cells: vec![ColChar::EMPTY; width],
+            placed_at: vec![0; width],
+            is_garbage: false,
+        }
+    }
+}
+
+/// The playfield and every cell placed on it, stored as one bitmask per row
+///
+/// Positions use the same coordinates as [`Block`](crate::Block)s: the playable columns are
+/// `1..=width` with walls at `x = 0` and `x = width + 1`, and the rows are `0..height` with the
+/// floor at `y = height`. Everything above the board (`y < 0`) is open space
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Board {
+    width: usize,
+    height: usize,
+    rows: Vec<Row>,
+    /// The frame newly filled cells are recorded as being placed on
+    frame: u64,
+}
+
+impl Board {
+    /// The widest board that fits in a row's bitmask
+    pub const MAX_WIDTH: usize = u64::BITS as usize;
+
+    /// Create an empty board
+    ///
+    /// # Panics
+    /// Panics if `width` is 0 or more than [`Board::MAX_WIDTH`]
+    #[must_use]
+    pub fn new(width: usize, height: usize) -> Self {
+        assert!(
+            (1..=Self::MAX_WIDTH).contains(&width),
+            "board width should be between 1 and {}",
+            Self::MAX_WIDTH
+        );
+
+        Self {
+            width,
+            height,
+            rows: vec![Row::new(width); height],
+            frame: 0,
+        }
+    }
+
+    #[must_use]
+    pub const fn width(&self) -> usize {
+        self.width
+    }
+
+    #[must_use]
+    pub const fn height(&self) -> usize {
+        self.height
+    }
+
+    const fn full_row_mask(&self) -> u64 {
+        if self.width == Self::MAX_WIDTH {
+            u64::MAX
+        } else {
+            (1 << self.width) - 1
+        }
+    }
+
+    /// Convert a position to a (row, column) index, or `None` if it's outside the board
+    fn index(&self, pos: Vec2D) -> Option<(usize, usize)> {
+        let column = usize::try_from(pos.x - 1).ok()?;
+        let row = usize::try_from(pos.y).ok()?;
+        (column < self.width && row < self.height).then_some((row, column))
+    }
+
+    /// Returns true if a placed cell occupies `pos`. Walls and the floor don't count
+    #[must_use]
+    pub fn is_filled(&self, pos: Vec2D) -> bool {
+        self.index(pos)
+            .is_some_and(|(row, column)| self.rows[row].mask & (1 << column) != 0)
+    }
+
+    /// The colour of the cell at `pos`, if it is filled
+    #[must_use]
+    pub fn get(&self, pos: Vec2D) -> Option<ColChar> {
+        self.index(pos)
+            .filter(|&(row, column)| self.rows[row].mask & (1 << column) != 0)
+            .map(|(row, column)| self.rows[row].cells[column])
+    }
+
+    /// The frame the cell at `pos` was filled on, if it is filled
+    #[must_use]
+    pub fn placed_at(&self, pos: Vec2D) -> Option<u64> {
+        self.index(pos)
+            .filter(|&(row, column)| self.rows[row].mask & (1 << column) != 0)
+            .map(|(row, column)| self.rows[row].placed_at[column])
+    }
+
+    /// Set the frame that cells filled from now on are recorded as being placed on
+    pub const fn set_frame(&mut self, frame: u64) {
+        self.frame = frame;
+    }
+
+    /// Fill the cell at `pos`. Positions outside the board are ignored
+    pub fn fill(&mut self, pos: Vec2D, colour: ColChar) {
+        if let Some((row, column)) = self.index(pos) {
+            let row = &mut self.rows[row];
+            row.mask |= 1 << column;
+            row.cells[column] = colour;
+            row.placed_at[column] = self.frame;
+        }
+    }
+
+    /// Returns true if no cells have been placed on the board
+    #[must_use]
+    pub fn is_empty(&self) -> bool {
+        self.rows.iter().all(|row| row.mask == 0)
+    }
+
+    /// Push a row of garbage in from the bottom, moving everything else up a row. Every column is
+    /// filled except `hole`
+    ///
+    /// Returns false if any filled cells were pushed off the top of the board
+    pub fn push_garbage_row(&mut self, hole: usize) -> bool {
+        let mut row = Row::new(self.width);
+        row.mask = self.full_row_mask() & !(1 << (hole - 1));
+        row.is_garbage = true;
+        row.placed_at.fill(self.frame);
+        for (column, cell) in row.cells.iter_mut().enumerate() {
+            if row.mask & (1 << column) != 0 {
+                *cell = GARBAGE_COLOUR;
+            }
+        }
+
+        let top_row = self.rows.remove(0);
+        self.rows.push(row);
+        top_row.mask == 0
+    }
+
+    /// The number of garbage rows that haven't been cleared yet
+    #[must_use]
+    pub fn garbage_rows(&self) -> usize {
+        self.rows.iter().filter(|row| row.is_garbage).count()
+    }
+
+    /// Remove every placed cell
+    pub fn clear(&mut self) {
+        self.rows.fill(Row::new(self.width));
+    }
+
+    /// Remove all filled lines, moving everything above them down, and return the number of
+    /// lines removed
+    pub fn clear_filled_lines(&mut self) -> i64 {
+        let full_row_mask = self.full_row_mask();
+        let previous_height = self.rows.len();
+        self.rows.retain(|row| row.mask != full_row_mask);
+
+        let cleared_lines = previous_height - self.rows.len();
+        self.rows
+            .splice(0..0, vec![Row::new(self.width); cleared_lines]);
+
+        cleared_lines as i64
+    }
+
+    /// Every filled cell on the board, along with its colour
+    pub fn cells(&self) -> impl Iterator<Item = (Vec2D, ColChar)> + '_ {
+        self.rows.iter().enumerate().flat_map(|(y, row)| {
+            (0..self.width)
+                .filter(move |x| row.mask & (1 << x) != 0)
+                .map(move |x| (Vec2D::new(x as i64 + 1, y as i64), row.cells[x]))
+        })
+    }
+
+    /// Draw the walls on either side of the board and the floor beneath it, leaving room for every
+    /// cell of the board to be drawn as a `scale` by `scale` square
+    pub fn draw_walls(&self, canvas: &mut impl Canvas, scale: i64) {
+        let right_wall = self.width as i64 * scale + 1;
+        let floor = self.height as i64 * scale;
+        for y in 0..=floor {
+            canvas.plot(Vec2D::new(0, y), ColChar::SOLID);
+            canvas.plot(Vec2D::new(right_wall, y), ColChar::SOLID);
+        }
+        for x in 1..right_wall {
+            canvas.plot(Vec2D::new(x, floor), ColChar::SOLID);
+        }
+    }
+}
+
+impl CanCollide for Board {
+    fn collides_with_pos(&self, pos: Vec2D) -> bool {
+        let is_wall = pos.x < 1 || pos.x > self.width as i64;
+        let is_floor = pos.y >= self.height as i64;
+        is_wall || is_floor || self.is_filled(pos)
+    }
+}
+
+/// Drawing to a `Board` fills the cells that were drawn to
+impl Canvas for Board {
+    fn plot(&mut self, pos: Vec2D, c: ColChar) {
+        self.fill(pos, c);
+    }
+}
+
+impl CanDraw for Board {
+    fn draw_to(&self, canvas: &mut impl Canvas) {
+        for (pos, colour) in self.cells() {
+            canvas.plot(pos, colour);
+        }
+        self.draw_walls(canvas, 1);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn positions(board: &Board) -> Vec<(i64, i64)> {
+        board.cells().map(|(pos, _)| (pos.x, pos.y)).collect()
+    }
+
+    fn fill_row(board: &mut Board, y: i64) {
+        for x in 1..=board.width() as i64 {
+            board.fill(Vec2D::new(x, y), ColChar::SOLID);
+        }
+    }
+
+    #[test]
+    fn full_rows_are_cleared_and_the_rows_above_fall() {
+        let mut board = Board::new(4, 6);
+        fill_row(&mut board, 5);
+        board.fill(Vec2D::new(2, 4), ColChar::SOLID);
+        fill_row(&mut board, 3);
+        board.fill(Vec2D::new(3, 2), ColChar::SOLID);
+
+        assert_eq!(board.clear_filled_lines(), 2);
+        assert_eq!(positions(&board), [(3, 4), (2, 5)]);
+        assert_eq!(board.clear_filled_lines(), 0);
+    }
+
+    #[test]
+    fn walls_and_the_floor_collide_but_the_space_above_doesnt() {
+        let board = Board::new(4, 6);
+
+        assert!(board.collides_with_pos(Vec2D::new(0, 3)));
+        assert!(board.collides_with_pos(Vec2D::new(5, 3)));
+        assert!(board.collides_with_pos(Vec2D::new(2, 6)));
+        assert!(!board.collides_with_pos(Vec2D::new(1, 3)));
+        assert!(!board.collides_with_pos(Vec2D::new(4, -2)));
+    }
+
+    #[test]
+    fn the_widest_board_works_up_to_its_edges() {
+        let width = Board::MAX_WIDTH as i64;
+        let mut board = Board::new(Board::MAX_WIDTH, 2);
+        board.fill(Vec2D::new(width, 0), ColChar::SOLID);
+        board.fill(Vec2D::new(width + 1, 0), ColChar::SOLID);
+
+        assert_eq!(positions(&board), [(width, 0)]);
+        assert!(board.collides_with_pos(Vec2D::new(width + 1, 0)));
+
+        fill_row(&mut board, 1);
+        assert_eq!(board.clear_filled_lines(), 1);
+        assert_eq!(positions(&board), [(width, 1)]);
+
+        assert!(board.push_garbage_row(width as usize));
+        assert!(board.is_filled(Vec2D::new(1, 1)));
+        assert!(!board.is_filled(Vec2D::new(width, 1)));
+    }
+
+    #[test]
+    fn garbage_pushes_the_stack_up_and_leaves_the_hole_empty() {
+        let mut board = Board::new(4, 3);
+        board.fill(Vec2D::new(1, 2), ColChar::SOLID);
+
+        assert!(board.push_garbage_row(3));
+        assert_eq!(positions(&board), [(1, 1), (1, 2), (2, 2), (4, 2)]);
+        assert_eq!(board.garbage_rows(), 1);
+
+        assert!(board.push_garbage_row(1));
+        // The placed cell is pushed off the top
+        assert!(!board.push_garbage_row(1));
+        assert_eq!(board.garbage_rows(), 3);
+    }
+}
@@ -0,0 +1,156 @@
+//! A computer opponent that plays an [`Engine`] through the same inputs as a player
+//!
+//! For every new piece the bot tries each rotation in each column it can reach, scores the board
+//! that would be left behind and then plays the inputs for the best placement one at a time
+use std::{collections::VecDeque, time::Duration};
+
+use gemini_engine::core::{ColChar, Vec2D};
+
+use crate::{engine::FRAME_DURATION, tetris_core, Block, Board, Engine, Input, Turn};
+
+/// How much each feature of the board left behind by a placement is worth. Tuned for guideline
+/// play by Yiyuan Lee's genetic algorithm
+const LINES_WEIGHT: f64 = 0.760_666;
+const HEIGHT_WEIGHT: f64 = -0.510_066;
+const HOLES_WEIGHT: f64 = -0.356_63;
+const BUMPINESS_WEIGHT: f64 = -0.184_483;
+
+/// Plays a game on its own, making one input every `move_delay`
+#[derive(Debug, Clone)]
+pub struct Bot {
+    move_delay: Duration,
+    time_since_move: Duration,
+    /// The inputs left to place the current piece
+    plan: VecDeque<Input>,
+    /// The number of pieces that had been placed when the plan was made
+    planned_at_piece: Option<u32>,
+}
+
+impl Bot {
+    #[must_use]
+    pub const fn new(move_delay: Duration) -> Self {
+        Self {
+            move_delay,
+            time_since_move: Duration::ZERO,
+            plan: VecDeque::new(),
+            planned_at_piece: None,
+        }
+    }
+
+    /// Make the bot's next input, if it's time to. Call once per frame, before [`Engine::tick`]
+    pub fn tick(&mut self, engine: &mut Engine) {
+        if let Some(input) = self.next_input(engine) {
+            engine.apply_input(input);
+            engine.release_input(input);
+        }
+    }
+
+    /// The input the bot wants to tap this frame, if any. Call once per frame
+    pub fn next_input(&mut self, engine: &Engine) -> Option<Input> {
+        if engine.is_game_over() {
+            return None;
+        }
+
+        // Plan again whenever a new piece spawns, including when the last one locked early
+        let pieces_placed = engine.stats().pieces_placed;
+        if self.planned_at_piece != Some(pieces_placed) {
+            self.plan = plan(engine);
+            self.planned_at_piece = Some(pieces_placed);
+        }
+
+        self.time_since_move += FRAME_DURATION;
+        if self.time_since_move < self.move_delay {
+            return None;
+        }
+        let input = self.plan.pop_front()?;
+        self.time_since_move = Duration::ZERO;
+        Some(input)
+    }
+}
+
+/// The inputs that move the active piece to the best placement found, ending with a hard drop
+fn plan(engine: &Engine) -> VecDeque<Input> {
+    let collision = engine.board();
+    let start = engine.active_block();
+    let mut best: Option<(f64, Vec<Input>)> = None;
+
+    for rotations in 0..start.shape.rotation_states.len() {
+        let mut block = start.clone();
+        let rotated = (0..rotations)
+            .all(|_| tetris_core::try_rotate_block(collision, &mut block, Turn::Clockwise));
+        if !rotated {
+            continue;
+        }
+
+        for (direction, input) in [(-1, Input::ShiftLeft), (1, Input::ShiftRight)] {
+            let mut inputs = vec![Input::RotateClockwise; rotations];
+            let mut shifted = block.clone();
+            loop {
+                let score = evaluate(&collision.stationary_blocks, &shifted);
+                if best
+                    .as_ref()
+                    .is_none_or(|(best_score, _)| score > *best_score)
+                {
+                    best = Some((score, inputs.clone()));
+                }
+
+                if !tetris_core::try_move_block(collision, &mut shifted, Vec2D::new(direction, 0)) {
+                    break;
+                }
+                inputs.push(input);
+            }
+        }
+    }
+
+    let mut inputs: VecDeque<_> = best.map(|(_, inputs)| inputs).unwrap_or_default().into();
+    inputs.push_back(Input::HardDrop);
+    inputs
+}
+
+/// Score the board left behind by hard dropping `block`. Higher is better
+fn evaluate(board: &Board, block: &Block) -> f64 {
+    let mut dropped = block.clone();
+    while tetris_core::try_move_block(board, &mut dropped, Vec2D::new(0, 1)) {}
+
+    let mut board = board.clone();
+    let cells = dropped.cells();
+    // Locking above the board loses the game
+    if cells.iter().any(|cell| cell.y < 0) {
+        return f64::NEG_INFINITY;
+    }
+    for cell in cells {
+        board.fill(cell, ColChar::SOLID);
+    }
+    let lines = board.clear_filled_lines();
+
+    let height = board.height() as i64;
+    let column_heights: Vec<i64> = (1..=board.width() as i64)
+        .map(|x| {
+            (0..height)
+                .find(|&y| board.is_filled(Vec2D::new(x, y)))
+                .map_or(0, |top| height - top)
+        })
+        .collect();
+    let holes = (1..=board.width() as i64)
+        .zip(&column_heights)
+        .map(|(x, &column_height)| {
+            (height - column_height..height)
+                .filter(|&y| !board.is_filled(Vec2D::new(x, y)))
+                .count()
+        })
+        .sum::<usize>();
+    let bumpiness: i64 = column_heights
+        .windows(2)
+        .map(|pair| (pair[0] - pair[1]).abs())
+        .sum();
+
+    let features = [
+        (LINES_WEIGHT, lines as f64),
+        (HEIGHT_WEIGHT, column_heights.iter().sum::<i64>() as f64),
+        (HOLES_WEIGHT, holes as f64),
+        (BUMPINESS_WEIGHT, bumpiness as f64),
+    ];
+    features
+        .into_iter()
+        .fold(0.0, |score, (weight, value)| weight.mul_add(value, score))
+}
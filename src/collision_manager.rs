@@ -0,0 +1,57 @@
+use gemini_engine::{
+    containers::CanCollide,
+    core::{CanDraw, Vec2D},
+};
+
+use crate::board::Board;
+
+#[derive(Clone)]
+pub struct CollisionManager {
+    pub stationary_blocks: Board,
+}
+
+impl CollisionManager {
+    #[must_use]
+    pub fn new(width: usize, height: usize) -> Self {
+        Self {
+            stationary_blocks: Board::new(width, height),
+        }
+    }
+
+    pub fn draw<E: CanDraw>(&mut self, element: &E) {
+        element.draw_to(&mut self.stationary_blocks);
+    }
+
+    // Remove all filled lines and return the number of lines filled and removed
+    pub fn clear_filled_lines(&mut self) -> i64 {
+        self.stationary_blocks.clear_filled_lines()
+    }
+
+    /// Push a row of garbage in from the bottom with a hole in column `hole`, moving the stationary
+    /// blocks up a row
+    ///
+    /// Returns false if any blocks were pushed off the top of the board
+    pub fn push_garbage_row(&mut self, hole: usize) -> bool {
+        self.stationary_blocks.push_garbage_row(hole)
+    }
+
+    /// Add an element to the stationary blocks and clear all full lines
+    ///
+    /// Returns the number of cleared lines
+    pub fn draw_and_clear_lines<E: CanDraw>(&mut self, block: &E) -> i64 {
+        self.draw(block);
+        self.clear_filled_lines()
+    }
+}
+
+impl CanCollide for CollisionManager {
+    fn collides_with_pos(&self, pos: Vec2D) -> bool {
+        self.stationary_blocks.collides_with_pos(pos)
+    }
+}
+
+impl CanDraw for CollisionManager {
+    fn draw_to(&self, canvas: &mut impl gemini_engine::core::Canvas) {
+        self.stationary_blocks.draw_to(canvas);
+    }
+}
@@ -0,0 +1,911 @@
+use std::{sync::Arc, time::Duration};
+
+use gemini_engine::core::Vec2D;
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    attack::{self, GarbageQueue},
+    block_manager::{tetris_core, Block, BlockManager, BlockType, Turn},
+    collision_manager::CollisionManager,
+    game_mode::{self, GameMode, GameResult},
+    garbage::GarbageGenerator,
+    handling::AutoShift,
+    level,
+    scoring::{LineClear, Scoring, HARD_DROP_SCORE, SOFT_DROP_SCORE},
+    stack_effect::{StackEffect, StackView, LINE_CLEAR_FLASH},
+    PieceSet, RandomizerKind, Settings, Stats,
+};
+
+/// The number of times per second [`Engine::tick`] is expected to be called
+pub const FPS: u32 = 60;
+/// The time that passes with every [`Engine::tick`], rounded up to the nearest nanosecond
+pub const FRAME_DURATION: Duration = Duration::from_nanos(1_000_000_000_u64.div_ceil(FPS as u64));
+
+/// An action the player can take on the active piece. Shifts and soft drops keep going for as
+/// long as they're held
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Input {
+    ShiftLeft,
+    ShiftRight,
+    RotateClockwise,
+    RotateAnticlockwise,
+    Rotate180,
+    SoftDrop,
+    HardDrop,
+    Hold,
+}
+
+/// A single game of Tetris. Inputs are pressed with [`Engine::apply_input`] and let go of with
+/// [`Engine::release_input`], and the game is advanced by one frame with [`Engine::tick`]
+#[derive(Clone)]
+pub struct Engine {
+    block_manager: BlockManager,
+    collision_manager: CollisionManager,
+    scoring: Scoring,
+    lines_cleared: u32,
+    /// The fraction of a row the active block has fallen since it last moved down
+    gravity_progress: f64,
+    auto_shift: AutoShift,
+    soft_drop_held: bool,
+    /// Set when soft drop is pressed, so that it applies for at least one frame even if it's
+    /// released before the next tick
+    soft_drop_pressed: bool,
+    result: Option<GameResult>,
+    garbage: GarbageGenerator,
+    /// The number of garbage rows pushed into the board so far
+    garbage_added: u32,
+    garbage_cleared: u32,
+    /// The time since garbage last rose in Survival
+    time_since_garbage_rose: Duration,
+    /// The frame lines were last cleared on
+    last_line_clear: Option<u64>,
+    /// Garbage sent by opponents that hasn't risen yet
+    garbage_queue: GarbageQueue,
+    /// Garbage lines to send to an opponent that haven't been taken yet
+    outgoing_attack: u32,
+    alerts: Vec<String>,
+    stats: Stats,
+    // Constants
+    mode: GameMode,
+    cheese_lines: u32,
+    /// Placed blocks pushed up to this row or higher are in the way of spawning pieces
+    spawn_area_bottom: i64,
+    stack_effect: StackEffect,
+    /// The number of characters each cell is drawn as, across and down
+    scale: i64,
+    starting_level: u32,
+    soft_drop_factor: f64,
+}
+
+impl Engine {
+    /// Create a new game. The piece sequence is fully determined by `seed`
+    #[must_use]
+    pub fn new(settings: &Settings, seed: u64) -> Self {
+        let mut engine = Self {
+            block_manager: BlockManager::new(settings, seed),
+            collision_manager: CollisionManager::new(
+                settings.field_width(),
+                settings.field_height(),
+            ),
+            scoring: Scoring::new(),
+            lines_cleared: 0,
+            gravity_progress: 0.0,
+            auto_shift: AutoShift::new(settings.handling),
+            soft_drop_held: false,
+            soft_drop_pressed: false,
+            result: None,
+            garbage: GarbageGenerator::new(settings.hole_placement, settings.field_width(), seed),
+            garbage_added: 0,
+            garbage_cleared: 0,
+            time_since_garbage_rose: Duration::ZERO,
+            last_line_clear: None,
+            garbage_queue: GarbageQueue::new(),
+            outgoing_attack: 0,
+            alerts: vec![],
+            stats: Stats::default(),
+            // Constants
+            mode: settings.mode,
+            cheese_lines: settings.cheese_lines,
+            spawn_area_bottom: settings.piece_set.spawn_area_bottom(settings.field_width()),
+            stack_effect: settings.stack_effect,
+            scale: settings.scale() as i64,
+            starting_level: settings.starting_level,
+            soft_drop_factor: settings.handling.soft_drop_factor,
+        };
+        engine.refill_cheese();
+        engine
+    }
+
+    /// Press an input. Shifts move the active piece immediately and start charging DAS. Inputs
+    /// are ignored once the game is over
+    pub fn apply_input(&mut self, input: Input) {
+        if self.is_game_over() {
+            return;
+        }
+
+        let collision = &self.collision_manager;
+
+        match input {
+            Input::ShiftLeft => {
+                self.block_manager
+                    .try_move_block(collision, Vec2D::new(-1, 0));
+                self.auto_shift.press(-1);
+            }
+            Input::ShiftRight => {
+                self.block_manager
+                    .try_move_block(collision, Vec2D::new(1, 0));
+                self.auto_shift.press(1);
+            }
+            Input::RotateAnticlockwise => {
+                self.block_manager
+                    .try_rotate_block(collision, Turn::Anticlockwise);
+            }
+            Input::RotateClockwise => {
+                self.block_manager
+                    .try_rotate_block(collision, Turn::Clockwise);
+            }
+            Input::Rotate180 => {
+                self.block_manager.try_rotate_block(collision, Turn::Half);
+            }
+            Input::SoftDrop => {
+                self.soft_drop_held = true;
+                self.soft_drop_pressed = true;
+            }
+            Input::HardDrop => {
+                self.block_manager.generate_ghost_block(collision);
+                self.scoring.add_drop(
+                    self.block_manager.ghost_block.pos.y - self.block_manager.block.pos.y,
+                    HARD_DROP_SCORE,
+                );
+                self.block_manager.block = self.block_manager.ghost_block.clone();
+                self.place_block();
+            }
+            Input::Hold => self.block_manager.hold(),
+        }
+    }
+
+    /// Let go of a held input. Only shifts and soft drops are affected
+    pub const fn release_input(&mut self, input: Input) {
+        match input {
+            Input::ShiftLeft => self.auto_shift.release(-1),
+            Input::ShiftRight => self.auto_shift.release(1),
+            Input::SoftDrop => self.soft_drop_held = false,
+            _ => (),
+        }
+    }
+
+    /// Advance the game by one frame, applying auto shift and gravity and placing the active piece once it has
+    /// rested on the ground for its lock delay
+    pub fn tick(&mut self) {
+        if self.is_game_over() {
+            return;
+        }
+        self.stats.frames += 1;
+        self.collision_manager
+            .stationary_blocks
+            .set_frame(self.stats.frames);
+        if self.mode.is_complete(self) {
+            self.result = Some(GameResult::Completed);
+            return;
+        }
+        if self.mode.has_rising_garbage() {
+            self.time_since_garbage_rose += FRAME_DURATION;
+            if self.time_since_garbage_rose >= game_mode::garbage_rise_interval(self.garbage_added)
+            {
+                self.time_since_garbage_rose = Duration::ZERO;
+                let hole = self.garbage.next_hole();
+                self.push_garbage([hole]);
+                if self.is_game_over() {
+                    return;
+                }
+            }
+        }
+
+        let is_soft_dropping = self.soft_drop_held || self.soft_drop_pressed;
+        self.soft_drop_pressed = false;
+        let gravity = if is_soft_dropping {
+            // Soft drop still works in modes without gravity
+            (level::gravity(self.level()) * self.soft_drop_factor).min(level::MAX_GRAVITY)
+        } else {
+            self.gravity()
+        };
+
+        let collision = &self.collision_manager;
+
+        // Repeat held shifts once DAS has charged
+        if let Some((direction, repeats)) = self.auto_shift.tick() {
+            for _ in 0..repeats {
+                if !self
+                    .block_manager
+                    .try_move_block(collision, Vec2D::new(direction, 0))
+                {
+                    break;
+                }
+            }
+        }
+
+        // Place the ghost block directly beneath the active block
+        self.block_manager.generate_ghost_block(collision);
+
+        // If the active block is on the floor...
+        if tetris_core::will_overlap(collision, &self.block_manager.block, Vec2D::new(0, 1)) {
+            // If the block's way down is blocked...
+            self.gravity_progress = 0.0;
+            if self.block_manager.lock_timer.tick_resting() {
+                self.place_block();
+            }
+        } else {
+            // At high gravity the block may fall several rows in a single frame
+            self.gravity_progress += gravity;
+            let rows_to_fall = self.gravity_progress.floor();
+            self.gravity_progress -= rows_to_fall;
+            for _ in 0..rows_to_fall as u32 {
+                if !self
+                    .block_manager
+                    .try_move_block(collision, Vec2D::new(0, 1))
+                {
+                    self.gravity_progress = 0.0;
+                    break;
+                }
+
+                // Increase score for soft drop
+                if is_soft_dropping {
+                    self.scoring.add_drop(1, SOFT_DROP_SCORE);
+                }
+            }
+        }
+    }
+
+    fn place_block(&mut self) {
+        let pre_clear_blocks = self.collision_manager.stationary_blocks.clone();
+
+        // If the current block is at the very top of the board...
+        if self.block_manager.reset() {
+            if self.mode.can_top_out() {
+                self.result = Some(GameResult::ToppedOut);
+                return;
+            }
+            // Modes without a game over start again from an empty board
+            self.collision_manager.stationary_blocks.clear();
+            self.alerts.push(String::from("Board Cleared"));
+            self.block_manager.generate_new_block();
+            return;
+        }
+
+        let t_spin = self.block_manager.t_spin(&pre_clear_blocks);
+        let level = self.level();
+        let garbage_rows = self.collision_manager.stationary_blocks.garbage_rows();
+        let cleared_lines = self
+            .collision_manager
+            .draw_and_clear_lines(&self.block_manager.block);
+        self.lines_cleared += cleared_lines as u32;
+        if cleared_lines > 0 {
+            self.last_line_clear = Some(self.stats.frames);
+        }
+        self.garbage_cleared +=
+            (garbage_rows - self.collision_manager.stationary_blocks.garbage_rows()) as u32;
+        self.stats.pieces_placed += 1;
+        if let Some(count) = (cleared_lines as usize)
+            .checked_sub(1)
+            .and_then(|i| self.stats.line_clears.get_mut(i))
+        {
+            *count += 1;
+        }
+
+        let clear = LineClear {
+            lines: cleared_lines,
+            t_spin,
+            perfect_clear: cleared_lines > 0 && self.collision_manager.stationary_blocks.is_empty(),
+        };
+        if clear.perfect_clear {
+            self.stats.perfect_clears += 1;
+        }
+        self.alerts.extend(self.scoring.award(clear, level));
+
+        // Attacks cancel queued garbage before any is sent
+        let is_back_to_back = clear.is_difficult() && self.scoring.back_to_back() > 0;
+        let attack = attack::attack(clear, is_back_to_back, self.scoring.combo());
+        let attack = self.garbage_queue.cancel(attack);
+        self.outgoing_attack += attack;
+        self.stats.lines_sent += attack;
+
+        if self.mode.is_complete(self) {
+            self.result = Some(GameResult::Completed);
+            return;
+        }
+        self.refill_cheese();
+        if cleared_lines == 0 {
+            self.raise_queued_garbage();
+            if self.is_game_over() {
+                return;
+            }
+        }
+        self.block_manager.generate_new_block();
+    }
+
+    /// Top the board back up with garbage in a Cheese Race, until every garbage row has been
+    /// added
+    fn refill_cheese(&mut self) {
+        if !self.mode.has_cheese() {
+            return;
+        }
+
+        let board = &self.collision_manager.stationary_blocks;
+        let visible_rows = self.mode.cheese_rows(board.height());
+        let missing_rows = visible_rows.saturating_sub(board.garbage_rows()) as u32;
+        // Garbage sent by opponents counts towards the rows added too
+        let remaining_rows = self.cheese_lines.saturating_sub(self.garbage_added);
+        let holes: Vec<_> = (0..missing_rows.min(remaining_rows))
+            .map(|_| self.garbage.next_hole())
+            .collect();
+        self.push_garbage(holes);
+    }
+
+    /// Raise the garbage queued by opponents, each attack with a hole of its own
+    fn raise_queued_garbage(&mut self) {
+        for lines in self.garbage_queue.take_rising() {
+            let hole = self.garbage.next_hole();
+            self.push_garbage(vec![hole; lines as usize]);
+            if self.is_game_over() {
+                return;
+            }
+        }
+    }
+
+    /// Push a row of garbage in from the bottom of the board for every hole, lifting the active
+    /// block out of the way if it's resting on the stack. The game is lost if the stack is pushed
+    /// into the spawn area
+    fn push_garbage(&mut self, holes: impl IntoIterator<Item = usize>) {
+        for hole in holes {
+            let overflowed = !self.collision_manager.push_garbage_row(hole);
+            self.garbage_added += 1;
+
+            let collision = &self.collision_manager;
+            if tetris_core::will_overlap(collision, &self.block_manager.block, Vec2D::ZERO) {
+                self.block_manager.push_up();
+            }
+
+            let in_spawn_area = collision
+                .stationary_blocks
+                .cells()
+                .any(|(pos, _)| pos.y <= self.spawn_area_bottom);
+            if overflowed || in_spawn_area {
+                self.result = Some(GameResult::ToppedOut);
+                return;
+            }
+        }
+
+        self.block_manager
+            .generate_ghost_block(&self.collision_manager);
+    }
+
+    /// Queue `lines` lines of garbage sent by an opponent. They rise once a piece locks without
+    /// clearing any lines, unless they're cancelled by clearing lines first
+    pub fn receive_garbage(&mut self, lines: u32) {
+        self.garbage_queue.push(lines);
+    }
+
+    /// Take the garbage lines sent since the last call, to pass on to an opponent
+    pub const fn take_attack(&mut self) -> u32 {
+        std::mem::replace(&mut self.outgoing_attack, 0)
+    }
+
+    /// The number of garbage lines waiting to rise
+    #[must_use]
+    pub fn incoming_garbage(&self) -> u32 {
+        self.garbage_queue.total()
+    }
+
+    /// End the game as the winner, for when every opponent has topped out
+    pub const fn win(&mut self) {
+        if self.result.is_none() {
+            self.result = Some(GameResult::Completed);
+        }
+    }
+
+    /// Drain the alerts (e.g. "Tetris!") generated since the last call
+    pub fn take_alerts(&mut self) -> Vec<String> {
+        std::mem::take(&mut self.alerts)
+    }
+
+    #[must_use]
+    pub const fn score(&self) -> i64 {
+        self.scoring.score()
+    }
+
+    /// The score along with the current back-to-back chain and combo
+    #[must_use]
+    pub const fn scoring(&self) -> &Scoring {
+        &self.scoring
+    }
+
+    /// The total number of lines cleared this game
+    #[must_use]
+    pub const fn lines_cleared(&self) -> u32 {
+        self.lines_cleared
+    }
+
+    /// The current level, which goes up every [`LINES_PER_LEVEL`](level::LINES_PER_LEVEL) lines
+    #[must_use]
+    pub const fn level(&self) -> u32 {
+        level::level_for_lines(self.starting_level, self.lines_cleared)
+    }
+
+    /// The current gravity, in rows per frame. Always 0 in modes without gravity
+    #[must_use]
+    pub fn gravity(&self) -> f64 {
+        if self.mode.has_gravity() {
+            level::gravity(self.level())
+        } else {
+            0.0
+        }
+    }
+
+    /// The seed the piece sequence was generated from
+    #[must_use]
+    pub const fn seed(&self) -> u64 {
+        self.block_manager.seed()
+    }
+
+    /// The randomizer the piece sequence is dealt by
+    #[must_use]
+    pub const fn randomizer(&self) -> RandomizerKind {
+        self.block_manager.randomizer()
+    }
+
+    #[must_use]
+    pub const fn piece_set(&self) -> &PieceSet {
+        self.block_manager.piece_set()
+    }
+
+    #[must_use]
+    pub const fn stats(&self) -> &Stats {
+        &self.stats
+    }
+
+    /// The time left until the next garbage row rises, in modes with rising garbage
+    #[must_use]
+    pub fn time_until_garbage_rises(&self) -> Option<Duration> {
+        self.mode.has_rising_garbage().then(|| {
+            game_mode::garbage_rise_interval(self.garbage_added)
+                .saturating_sub(self.time_since_garbage_rose)
+        })
+    }
+
+    /// The number of garbage rows cleared this game
+    #[must_use]
+    pub const fn garbage_cleared(&self) -> u32 {
+        self.garbage_cleared
+    }
+
+    /// The number of garbage rows to dig through in a Cheese Race
+    #[must_use]
+    pub const fn cheese_lines(&self) -> u32 {
+        self.cheese_lines
+    }
+
+    #[must_use]
+    pub const fn mode(&self) -> GameMode {
+        self.mode
+    }
+
+    #[must_use]
+    pub const fn is_game_over(&self) -> bool {
+        self.result.is_some()
+    }
+
+    /// How the game ended, or `None` if it's still going
+    #[must_use]
+    pub const fn result(&self) -> Option<GameResult> {
+        self.result
+    }
+
+    /// The walls and the blocks that have already been placed
+    #[must_use]
+    pub const fn board(&self) -> &CollisionManager {
+        &self.collision_manager
+    }
+
+    /// The walls and placed blocks as the player should see them, with the stack effect applied
+    #[must_use]
+    pub fn visible_board(&self) -> StackView<'_> {
+        let flash_frames = (LINE_CLEAR_FLASH.as_secs_f64() * f64::from(FPS)) as u64;
+        StackView {
+            board: &self.collision_manager.stationary_blocks,
+            effect: self.stack_effect,
+            frame: self.stats.frames,
+            flash: self
+                .last_line_clear
+                .is_some_and(|frame| self.stats.frames - frame < flash_frames),
+            scale: self.scale,
+        }
+    }
+
+    /// The number of characters each cell is drawn as, across and down. 2 in Big mode
+    #[must_use]
+    pub const fn scale(&self) -> i64 {
+        self.scale
+    }
+
+    #[must_use]
+    pub const fn active_block(&self) -> &Block {
+        &self.block_manager.block
+    }
+
+    #[must_use]
+    pub const fn ghost_block(&self) -> &Block {
+        &self.block_manager.ghost_block
+    }
+
+    #[must_use]
+    pub const fn held_piece(&self) -> Option<&Arc<BlockType>> {
+        self.block_manager.held_piece.as_ref()
+    }
+
+    /// The upcoming pieces, starting with the next one to spawn
+    pub fn queue(&self) -> impl Iterator<Item = &Arc<BlockType>> {
+        self.block_manager.queue()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use gemini_engine::core::ColChar;
+
+    use super::*;
+
+    /// A new game with the named piece in play instead of the first one dealt
+    fn engine_with(name: &str) -> Engine {
+        engine_in(GameMode::default(), name)
+    }
+
+    /// A new game of `mode` with the named piece in play instead of the first one dealt
+    fn engine_in(mode: GameMode, name: &str) -> Engine {
+        let settings = Settings {
+            mode,
+            cheese_lines: 1,
+            ..Settings::default()
+        };
+        let mut engine = Engine::new(&settings, 0);
+        let piece = engine
+            .piece_set()
+            .pieces()
+            .iter()
+            .find(|piece| piece.name == name)
+            .map(Arc::clone)
+            .expect("every tetromino should be in the default piece set");
+        engine.block_manager.block = Block::spawn(piece, settings.board_width);
+        engine
+    }
+
+    fn fill(engine: &mut Engine, cells: impl IntoIterator<Item = (i64, i64)>) {
+        for (x, y) in cells {
+            engine
+                .collision_manager
+                .stationary_blocks
+                .fill(Vec2D::new(x, y), ColChar::SOLID);
+        }
+    }
+
+    fn names<'a>(pieces: impl Iterator<Item = &'a Arc<BlockType>>) -> Vec<&'a str> {
+        pieces.map(|piece| piece.name.as_str()).collect()
+    }
+
+    #[test]
+    fn pieces_spawn_at_the_top_in_the_order_dealt_by_the_seed() {
+        let settings = Settings::default();
+        let engine = Engine::new(&settings, 7);
+
+        assert!(engine.active_block().cells().iter().all(|cell| {
+            (1..=settings.board_width as i64).contains(&cell.x)
+                && (0..=engine.spawn_area_bottom).contains(&cell.y)
+        }));
+        assert_eq!(engine.queue().count(), settings.piece_preview_count);
+        assert_eq!(engine.held_piece(), None);
+        assert_eq!(engine.result(), None);
+
+        let same_seed = Engine::new(&settings, 7);
+        assert_eq!(
+            engine.active_block().shape.name,
+            same_seed.active_block().shape.name
+        );
+        assert_eq!(names(engine.queue()), names(same_seed.queue()));
+    }
+
+    #[test]
+    fn hard_drop_places_the_piece_and_clears_full_lines() {
+        let mut engine = engine_with("I");
+        let columns: Vec<i64> = engine
+            .active_block()
+            .cells()
+            .iter()
+            .map(|cell| cell.x)
+            .collect();
+        // Everything but the I piece's columns on the bottom row, and one cell above that
+        fill(
+            &mut engine,
+            (1..=10)
+                .filter(|x| !columns.contains(x))
+                .map(|x| (x, 19))
+                .chain([(1, 18)]),
+        );
+        let next = engine.queue().next().map(|piece| piece.name.clone());
+
+        engine.apply_input(Input::HardDrop);
+
+        assert_eq!(engine.lines_cleared(), 1);
+        assert_eq!(engine.stats().pieces_placed, 1);
+        let cells: Vec<Vec2D> = engine
+            .board()
+            .stationary_blocks
+            .cells()
+            .map(|(pos, _)| pos)
+            .collect();
+        assert_eq!(cells, [Vec2D::new(1, 19)]);
+        assert!(engine.take_alerts().contains(&String::from("Single!")));
+        assert_eq!(Some(engine.active_block().shape.name.clone()), next);
+    }
+
+    #[test]
+    fn hold_swaps_the_active_piece_once_per_piece() {
+        let mut engine = Engine::new(&Settings::default(), 3);
+        let first = engine.active_block().shape.name.clone();
+        let second = engine.queue().next().map(|piece| piece.name.clone());
+
+        engine.apply_input(Input::Hold);
+        assert_eq!(engine.held_piece().map(|piece| &piece.name), Some(&first));
+        assert_eq!(Some(engine.active_block().shape.name.clone()), second);
+
+        // Holding again before placing the piece does nothing
+        engine.apply_input(Input::Hold);
+        assert_eq!(engine.held_piece().map(|piece| &piece.name), Some(&first));
+        assert_eq!(Some(engine.active_block().shape.name.clone()), second);
+
+        engine.apply_input(Input::HardDrop);
+        engine.apply_input(Input::Hold);
+        assert_eq!(engine.active_block().shape.name, first);
+    }
+
+    #[test]
+    fn stacking_to_the_top_ends_the_game() {
+        let mut engine = Engine::new(&Settings::default(), 0);
+        for _ in 0..100 {
+            if engine.is_game_over() {
+                break;
+            }
+            engine.apply_input(Input::HardDrop);
+        }
+        assert_eq!(engine.result(), Some(GameResult::ToppedOut));
+
+        // Nothing can be played once the game is over
+        let pieces_placed = engine.stats().pieces_placed;
+        engine.apply_input(Input::HardDrop);
+        engine.tick();
+        assert_eq!(engine.stats().pieces_placed, pieces_placed);
+        assert_eq!(engine.stats().frames, 0);
+    }
+
+    #[test]
+    fn a_copied_game_plays_out_the_same_as_the_original() {
+        let mut engine = Engine::new(&Settings::default(), 11);
+        engine.apply_input(Input::HardDrop);
+        let mut copy = engine.clone();
+
+        for engine in [&mut engine, &mut copy] {
+            for input in [
+                Input::ShiftLeft,
+                Input::HardDrop,
+                Input::RotateClockwise,
+                Input::Hold,
+                Input::HardDrop,
+                Input::Hold,
+            ] {
+                engine.apply_input(input);
+                engine.release_input(input);
+                for _ in 0..5 {
+                    engine.tick();
+                }
+            }
+        }
+
+        assert_eq!(
+            engine.board().stationary_blocks,
+            copy.board().stationary_blocks
+        );
+        // Debug shows whether a block is a ghost, which copying has to keep
+        assert_eq!(
+            format!("{:?}", engine.active_block()),
+            format!("{:?}", copy.active_block())
+        );
+        assert_eq!(
+            format!("{:?}", engine.ghost_block()),
+            format!("{:?}", copy.ghost_block())
+        );
+        assert_eq!(names(engine.queue()), names(copy.queue()));
+        assert_eq!(engine.held_piece(), copy.held_piece());
+        assert_eq!(engine.score(), copy.score());
+    }
+
+    fn cheese_race(cheese_lines: u32) -> Engine {
+        let settings = Settings {
+            mode: GameMode::CheeseRace,
+            cheese_lines,
+            ..Settings::default()
+        };
+        Engine::new(&settings, 0)
+    }
+
+    fn garbage_rows(engine: &Engine) -> usize {
+        engine.board().stationary_blocks.garbage_rows()
+    }
+
+    #[test]
+    fn cheese_races_are_topped_up_until_every_row_has_been_added() {
+        let mut engine = cheese_race(12);
+        assert_eq!(garbage_rows(&engine), game_mode::CHEESE_RACE_VISIBLE_ROWS);
+
+        engine.collision_manager.stationary_blocks.clear();
+        engine.refill_cheese();
+        assert_eq!(garbage_rows(&engine), 2);
+
+        engine.collision_manager.stationary_blocks.clear();
+        engine.refill_cheese();
+        assert_eq!(garbage_rows(&engine), 0);
+    }
+
+    #[test]
+    fn garbage_from_opponents_counts_towards_the_cheese_rows() {
+        let mut engine = cheese_race(12);
+        engine.receive_garbage(1);
+        engine.raise_queued_garbage();
+        assert_eq!(garbage_rows(&engine), 11);
+
+        engine.collision_manager.stationary_blocks.clear();
+        engine.refill_cheese();
+        assert_eq!(garbage_rows(&engine), 1);
+    }
+
+    #[test]
+    fn rising_garbage_lifts_the_active_piece_off_the_stack() {
+        let mut engine = engine_with("O");
+        let spawned_at = engine.active_block().pos;
+        engine.push_garbage([1]);
+        // Pieces in the air are left where they are
+        assert_eq!(engine.active_block().pos, spawned_at);
+
+        while engine
+            .block_manager
+            .try_move_block(&engine.collision_manager, Vec2D::new(0, 1))
+        {}
+        let resting_at = engine.active_block().pos;
+        engine.push_garbage([1]);
+
+        assert_eq!(
+            engine.active_block().pos,
+            Vec2D::new(resting_at.x, resting_at.y - 1)
+        );
+        assert!(!tetris_core::will_overlap(
+            &engine.collision_manager,
+            engine.active_block(),
+            Vec2D::ZERO
+        ));
+        assert_eq!(garbage_rows(&engine), 2);
+        assert_eq!(engine.result(), None);
+    }
+
+    #[test]
+    fn clearing_lines_cancels_incoming_garbage_before_it_rises() {
+        let mut engine = engine_with("O");
+        engine.receive_garbage(2);
+        let columns: Vec<i64> = engine
+            .active_block()
+            .cells()
+            .iter()
+            .map(|cell| cell.x)
+            .collect();
+        // Two rows but the O piece's columns, and a cell above them so the clear isn't perfect
+        fill(
+            &mut engine,
+            (1..=10)
+                .filter(|x| !columns.contains(x))
+                .flat_map(|x| [(x, 18), (x, 19)])
+                .chain([(1, 17)]),
+        );
+
+        // The double's one line of attack cancels one incoming line, and nothing is sent
+        engine.apply_input(Input::HardDrop);
+        assert_eq!(engine.lines_cleared(), 2);
+        assert_eq!(engine.incoming_garbage(), 1);
+        assert_eq!(engine.take_attack(), 0);
+        assert_eq!(engine.stats().lines_sent, 0);
+        assert_eq!(garbage_rows(&engine), 0);
+
+        // The rest rises once a piece locks without clearing anything
+        engine.apply_input(Input::HardDrop);
+        assert_eq!(engine.incoming_garbage(), 0);
+        assert_eq!(garbage_rows(&engine), 1);
+    }
+
+    /// Fill the bottom row apart from the columns the active piece will drop into
+    fn fill_under_active_piece(engine: &mut Engine) {
+        let columns: Vec<i64> = engine
+            .active_block()
+            .cells()
+            .iter()
+            .map(|cell| cell.x)
+            .collect();
+        fill(
+            engine,
+            (1..=10).filter(|x| !columns.contains(x)).map(|x| (x, 19)),
+        );
+    }
+
+    #[test]
+    fn sprints_end_on_the_last_line() {
+        let mut engine = engine_in(GameMode::Sprint, "I");
+        engine.lines_cleared = game_mode::SPRINT_LINES - 1;
+        engine.tick();
+        assert_eq!(engine.result(), None);
+
+        fill_under_active_piece(&mut engine);
+        engine.apply_input(Input::HardDrop);
+        assert_eq!(engine.lines_cleared(), game_mode::SPRINT_LINES);
+        assert_eq!(engine.result(), Some(GameResult::Completed));
+    }
+
+    #[test]
+    fn ultra_ends_when_the_time_runs_out() {
+        let mut engine = engine_in(GameMode::Ultra, "I");
+        let limit = game_mode::ULTRA_TIME_LIMIT
+            .as_nanos()
+            .div_ceil(FRAME_DURATION.as_nanos());
+        engine.stats.frames = limit as u64 - 2;
+
+        engine.tick();
+        assert_eq!(engine.result(), None);
+        engine.tick();
+        assert_eq!(engine.result(), Some(GameResult::Completed));
+    }
+
+    #[test]
+    fn cheese_races_end_once_every_garbage_row_is_cleared() {
+        let mut engine = engine_in(GameMode::CheeseRace, "I");
+        assert_eq!(garbage_rows(&engine), 1);
+        let hole = (1..=10)
+            .find(|&x| {
+                !engine
+                    .board()
+                    .stationary_blocks
+                    .is_filled(Vec2D::new(x, 19))
+            })
+            .expect("the garbage row should have a hole");
+        fill(&mut engine, [(hole, 19)]);
+        engine.tick();
+        assert_eq!(engine.result(), None);
+
+        engine.apply_input(Input::HardDrop);
+        assert_eq!(engine.garbage_cleared(), 1);
+        assert_eq!(engine.result(), Some(GameResult::Completed));
+    }
+
+    #[test]
+    fn survival_garbage_rises_on_its_timer() {
+        let mut engine = engine_in(GameMode::Survival, "I");
+        let first_rise = game_mode::SURVIVAL_FIRST_RISE
+            .as_nanos()
+            .div_ceil(FRAME_DURATION.as_nanos()) as u64;
+
+        for _ in 1..first_rise {
+            engine.tick();
+        }
+        assert_eq!(garbage_rows(&engine), 0);
+        engine.tick();
+        assert_eq!(garbage_rows(&engine), 1);
+        // The next row comes sooner
+        assert_eq!(
+            engine.time_until_garbage_rises(),
+            Some(game_mode::garbage_rise_interval(1))
+        );
+    }
+}
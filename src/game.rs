@@ -3,236 +3,400 @@ use std::{
     time::{Duration, Instant},
 };
 
-use console_input::keypress::exit_raw_mode;
 use crossterm::{
     cursor::MoveTo,
     event::{Event, KeyCode, KeyEvent, KeyEventKind},
     execute,
     terminal::{Clear, ClearType},
 };
-use gemini_engine::{
-    ascii::{Sprite, Text},
-    core::{ColChar, Modifier, Vec2D},
-    gameloop::MainLoopRoot,
-    view::View,
-};
+use gemini_engine::{gameloop::MainLoopRoot, view::View};
+use tetris::{game_mode::GameResult, Engine, Input, Settings};
 
 mod alerts;
-mod block_manager;
-mod collision_manager;
+mod board_state;
+mod keyboard;
+mod layout;
+mod online;
 mod pause;
-use crate::audio::AudioManager;
+mod screens;
+mod spectate;
+mod versus;
+mod watch;
+use crate::{
+    args::random_seed,
+    audio::AudioManager,
+    net::{Connection, InputEvent},
+};
 use alerts::AlertDisplay;
-use block_manager::BlockManager;
-use collision_manager::CollisionManager;
+use board_state::BoardState;
+use keyboard::KeyMap;
+use layout::Layout;
+use online::Online;
 use pause::pause;
-
-use self::alerts::generate_alert_for_filled_lines;
+use screens::Screen;
+use spectate::PlayerState;
+pub use spectate::{Feed, Spectators};
+use versus::Opponents;
+pub use watch::Watch;
+
+/// Someone playing on their own board with their own keys
+struct Player {
+    engine: Engine,
+    alert_display: AlertDisplay,
+    /// `None` for an opponent playing on another machine
+    key_map: Option<KeyMap>,
+}
 
 pub struct Game {
     view: View,
-    alert_display: AlertDisplay,
-    block_manager: BlockManager,
-    collision_manager: CollisionManager,
-    score: i64,
-    t: usize,
+    players: Vec<Player>,
+    opponents: Opponents,
+    /// The connection to the second player, if they're on another machine
+    online: Option<Online>,
+    /// Everyone watching the game, if it's being streamed
+    spectators: Option<Spectators>,
+    screen: Screen,
     // Constants
-    controls_help_text: String,
+    settings: Settings,
+    layout: Layout,
     audio_manager: AudioManager,
     last_volume_adjust: Instant,
+    /// Whether the terminal reports key releases. If not, every key press is treated as a tap
+    reports_key_releases: bool,
 }
 
 impl Game {
+    /// Set up a game. Given a connection, the game starts straight away against the player on the
+    /// other end of it, whose board is drawn where a second player's would be. Given spectators,
+    /// everything on screen is streamed to them
     pub fn new(
-        block_place_cooldown: u32,
-        piece_preview_count: usize,
-        controls_help_text: &str,
+        settings: &Settings,
+        seed: u64,
+        connection: Option<Connection>,
+        spectators: Option<Spectators>,
     ) -> Self {
         let audio_manager = AudioManager::new();
-        Self {
-            view: View::new(50, 21, ColChar::EMPTY),
-            alert_display: AlertDisplay::new(Vec2D::new(12, 7)),
-            block_manager: BlockManager::new(block_place_cooldown, piece_preview_count),
-            collision_manager: CollisionManager::new(),
-            score: 0,
-            t: 0,
+        let opponents = Opponents::new(settings, seed);
+        let layout = Layout::new(
+            settings,
+            settings.players + usize::from(connection.is_some()),
+            opponents.len(),
+        );
+
+        let mut game = Self {
+            view: layout.view(),
+            players: vec![],
+            opponents,
+            online: None,
+            spectators,
+            screen: Screen::Menu,
             // Constants
-            controls_help_text: controls_help_text.to_string(),
+            settings: settings.clone(),
+            layout,
             audio_manager,
             last_volume_adjust: Instant::now(),
+            reports_key_releases: keyboard::enable_key_release_events(),
+        };
+        game.players = game.new_players(seed);
+        if let Some(connection) = connection {
+            let opponent_settings = Settings {
+                handling: connection.opponent_handling(),
+                ..settings.clone()
+            };
+            let engine = Engine::new(&opponent_settings, seed);
+            game.online = Some(Online::new(connection, &engine, settings.input_delay));
+            game.players.push(Player {
+                engine,
+                alert_display: game.layout.alert_display(1),
+                key_map: None,
+            });
+            game.screen = Screen::Playing;
         }
+        game
     }
-}
 
-impl MainLoopRoot for Game {
-    type InputDataType = Event;
-
-    fn get_fps(&self) -> f32 {
-        60.0
+    /// Set up a board for every player. Players after the first are dealt their own sequence of
+    /// pieces, unless the seed is shared
+    fn new_players(&self, seed: u64) -> Vec<Player> {
+        KeyMap::for_players(self.settings.players)
+            .into_iter()
+            .enumerate()
+            .map(|(i, key_map)| {
+                let seed = if self.settings.shared_seed {
+                    seed
+                } else {
+                    seed.wrapping_add(i as u64)
+                };
+                Player {
+                    engine: Engine::new(&self.settings, seed),
+                    alert_display: self.layout.alert_display(i),
+                    key_map: Some(key_map),
+                }
+            })
+            .collect()
     }
 
-    fn frame(&mut self, input_data: Option<Self::InputDataType>) {
-        self.t += 1;
-        let mut block_speed = 12;
+    /// The seed the game was started with, which is also the first player's
+    fn seed(&self) -> u64 {
+        self.players[0].engine.seed()
+    }
 
-        // Generate a collision with the current walls and placed blocks
-        let collision = self.collision_manager.get();
+    /// Start a new game with the given seed
+    fn restart(&mut self, seed: u64) {
+        self.players = self.new_players(seed);
+        self.opponents = Opponents::new(&self.settings, seed);
+        self.screen = Screen::Playing;
+    }
 
-        // Handle Inputs
-        if let Some(Event::Key(KeyEvent {
-            code,
-            kind: KeyEventKind::Press,
-            ..
-        })) = input_data
-        {
-            let now = Instant::now();
-
-            match code {
-                // Pause
-                KeyCode::Esc => {
-                    self.view.clear();
-                    self.view.display_render().expect("Failed to clear screen");
-                    self.audio_manager.toggle();
-                    pause();
-                    self.audio_manager.toggle();
-                }
+    /// Handle a key press on the menu or game over screen
+    fn handle_screen_key(&mut self, code: KeyCode) {
+        // An online game can't be restarted without the other player
+        if self.online.is_some() {
+            if matches!(code, KeyCode::Char('q') | KeyCode::Esc) {
+                keyboard::exit();
+            }
+            return;
+        }
 
-                // Shift left
-                KeyCode::Left => {
-                    self.block_manager
-                        .try_move_block(&collision, Vec2D::new(-1, 0));
-                }
+        match (self.screen, code) {
+            (Screen::Menu, KeyCode::Enter) | (Screen::GameOver, KeyCode::Char('r')) => {
+                self.restart(self.seed());
+            }
+            (Screen::GameOver, KeyCode::Char('n')) => self.restart(random_seed()),
+            (Screen::GameOver, KeyCode::Char('m')) => self.screen = Screen::Menu,
+            (Screen::Menu | Screen::GameOver, KeyCode::Char('q') | KeyCode::Esc) => {
+                keyboard::exit();
+            }
+            _ => (),
+        }
+    }
 
-                // Shift right
-                KeyCode::Right => {
-                    self.block_manager
-                        .try_move_block(&collision, Vec2D::new(1, 0));
+    fn handle_key(&mut self, code: KeyCode, kind: KeyEventKind) {
+        // Find whose board the key belongs to
+        let target = self.players.iter().enumerate().find_map(|(i, player)| {
+            player
+                .key_map
+                .and_then(|key_map| key_map.input(code))
+                .map(|input| (i, input))
+        });
+
+        match kind {
+            KeyEventKind::Press => (),
+            KeyEventKind::Release => {
+                if let Some((i, input)) = target {
+                    self.play_input(i, InputEvent::Release(input));
                 }
+                return;
+            }
+            // Held inputs are repeated by the engine
+            KeyEventKind::Repeat => return,
+        }
 
-                // Rotate anti-clockwise
-                KeyCode::Char('z') => {
-                    self.block_manager.try_rotate_block(&collision, false);
-                }
+        if let Some((i, input)) = target {
+            self.play_input(i, InputEvent::Press(input));
+            if !self.reports_key_releases {
+                self.play_input(i, InputEvent::Release(input));
+            }
+            return;
+        }
 
-                // Rotate clockwise
-                KeyCode::Up | KeyCode::Char('x') => {
-                    self.block_manager.try_rotate_block(&collision, true);
+        let now = Instant::now();
+        match code {
+            // Pause. An online game can't be paused, as the other player would be left waiting
+            KeyCode::Esc if self.online.is_none() => {
+                self.view.clear();
+                self.view.display_render().expect("Failed to clear screen");
+                self.audio_manager.toggle();
+                pause();
+                self.audio_manager.toggle();
+
+                // Any releases were swallowed while paused
+                for player in &mut self.players {
+                    for input in [Input::ShiftLeft, Input::ShiftRight, Input::SoftDrop] {
+                        player.engine.release_input(input);
+                    }
                 }
+            }
 
-                // Soft drop
-                KeyCode::Down => block_speed = 2,
-
-                // Hard drop
-                KeyCode::Char(' ') => {
-                    self.score +=
-                        self.block_manager.ghost_block.pos.y - self.block_manager.block.pos.y;
-                    self.block_manager.block = self.block_manager.ghost_block.clone();
-                    self.t = block_speed - 1;
-                    self.block_manager.placing_cooldown = 1;
-                }
+            KeyCode::Char('+' | '=')
+                if now.duration_since(self.last_volume_adjust) > Duration::from_millis(100) =>
+            {
+                self.audio_manager.increase_volume(0.1);
+                self.last_volume_adjust = now;
+            }
+            KeyCode::Char('-')
+                if now.duration_since(self.last_volume_adjust) > Duration::from_millis(100) =>
+            {
+                self.audio_manager.decrease_volume(0.1);
+                self.last_volume_adjust = now;
+            }
 
-                KeyCode::Char('c') => self.block_manager.hold(),
+            _ => (),
+        }
+    }
 
-                KeyCode::Char('+') | KeyCode::Char('=') => {
-                    if now.duration_since(self.last_volume_adjust) > Duration::from_millis(100) {
-                        self.audio_manager.increase_volume(0.1);
-                        self.last_volume_adjust = now;
-                    }
-                }
-                KeyCode::Char('-') => {
-                    if now.duration_since(self.last_volume_adjust) > Duration::from_millis(100) {
-                        self.audio_manager.decrease_volume(0.1);
-                        self.last_volume_adjust = now;
-                    }
-                }
+    /// Play an input on player `i`'s board. In an online game it's sent to the other player and
+    /// played once it's due instead
+    fn play_input(&mut self, i: usize, event: InputEvent) {
+        match &mut self.online {
+            Some(online) => online.record(event),
+            None => event.play_on(&mut self.players[i].engine),
+        }
+    }
 
-                _ => (),
+    /// Play a frame of the game in progress. Returns the alerts that came up on each player's
+    /// board
+    fn play_frame(&mut self) -> Vec<Vec<String>> {
+        let mut alerts = vec![vec![]; self.players.len()];
+        if let (Some(online), [player, opponent]) = (&mut self.online, &mut self.players[..]) {
+            alerts[1] = online.frame(&mut player.engine, &mut opponent.engine);
+        }
+        // Boards played on another machine are ticked as their frames arrive
+        for player in &mut self.players {
+            if player.key_map.is_some() {
+                player.engine.tick();
+            }
+        }
+        self.opponents.frame(
+            self.players
+                .iter_mut()
+                .filter(|player| player.key_map.is_some())
+                .map(|player| &mut player.engine),
+        );
+        for (player, alerts) in self.players.iter_mut().zip(&mut alerts) {
+            alerts.extend(player.engine.take_alerts());
+            for alert in alerts.iter() {
+                player.alert_display.push(alert);
             }
         }
 
-        // Place the ghost block directly beneath the active block
-        self.block_manager.generate_ghost_block(&collision);
-
-        // If the active block is on the floor...
-        if collision.will_overlap_element(&self.block_manager.block, Vec2D::new(0, 1)) {
-            // If the block's way down is blocked...
-            self.block_manager.placing_cooldown -= 1;
-            if self.block_manager.placing_cooldown == 0 {
-                let pre_clear_blocks = self.collision_manager.stationary_blocks.clone();
+        // The game ends as soon as one player finishes or once every player here is out. Online,
+        // it ends once both machines can tell who won, or when the other player leaves
+        let finished = self
+            .players
+            .iter()
+            .any(|player| player.engine.result() == Some(GameResult::Completed));
+        let everyone_out = self
+            .players
+            .iter()
+            .filter(|player| player.key_map.is_some())
+            .all(|player| player.engine.is_game_over());
+        let over = self
+            .online
+            .as_ref()
+            .map_or(finished || everyone_out, |online| {
+                online.outcome().is_some() || !online.is_connected()
+            });
+        if over {
+            self.screen = Screen::GameOver;
+        }
 
-                // If the current block is at the very top of the board...
-                if self.block_manager.reset() {
-                    println!("Game over!\r");
-                    exit_raw_mode();
-                }
+        alerts
+    }
 
-                let cleared_lines = self
-                    .collision_manager
-                    .draw_and_clear_lines(&self.block_manager.block);
-
-                // Display an appropriate alert
-                self.alert_display.priorised_alerts_with_score(
-                    &[
-                        self.block_manager
-                            .check_for_t_spin(&pre_clear_blocks, cleared_lines),
-                        generate_alert_for_filled_lines(cleared_lines),
-                    ],
-                    &mut self.score,
-                );
-
-                self.block_manager.generate_new_block();
-            }
-        } else if self.t % block_speed == 0 {
-            // move down and increase score for soft drop
-            self.block_manager
-                .try_move_block(&collision, Vec2D::new(0, 1));
-            if block_speed == 2 {
-                self.score += 1;
+    /// The text shown on the menu and game over screens, or `None` while the game is played
+    fn screen_text(&self) -> Option<String> {
+        match self.screen {
+            Screen::Menu => Some(screens::menu_text(self.settings.mode)),
+            Screen::Playing => None,
+            Screen::GameOver => {
+                let engines: Vec<&Engine> =
+                    self.players.iter().map(|player| &player.engine).collect();
+                Some(match (&self.online, &engines[..]) {
+                    (Some(online), [player, opponent]) => screens::online_over_text(
+                        player,
+                        opponent,
+                        online.outcome(),
+                        online.is_connected(),
+                    ),
+                    (_, [engine]) => screens::game_over_text(engine),
+                    _ => screens::versus_over_text(&engines),
+                })
             }
         }
     }
 
-    fn render_frame(&mut self) {
-        self.view.clear();
+    /// Send spectators what's on screen this frame, along with the `alerts` that came up on each
+    /// player's board
+    fn spectate(&mut self, alerts: Vec<Vec<String>>) {
+        let message = match self.screen_text() {
+            Some(text) => spectate::Message::Screen { text },
+            None => spectate::Message::Frame {
+                players: self
+                    .players
+                    .iter()
+                    .zip(alerts)
+                    .map(|(player, alerts)| PlayerState {
+                        board: BoardState::new(&player.engine),
+                        alerts,
+                    })
+                    .collect(),
+                bots: self.opponents.states(),
+            },
+        };
+
+        if let Some(spectators) = &mut self.spectators {
+            spectators.frame(self.layout, &message);
+        }
+    }
 
-        // Blit the walls and stationary blocks
-        self.view.draw_double_width(&self.collision_manager);
+    /// Draw the boards and HUDs of the game in progress
+    fn draw_game(&mut self) {
+        for (i, player) in self.players.iter_mut().enumerate() {
+            let help_text = player.key_map.map_or("Opponent", KeyMap::help_text);
+            self.layout.draw_player(
+                &mut self.view,
+                i,
+                &BoardState::new(&player.engine),
+                help_text,
+            );
+            self.view.draw(&player.alert_display);
+            player.alert_display.frame();
+        }
+        self.layout
+            .draw_bots(&mut self.view, &self.opponents.states());
+    }
+}
 
-        self.view.draw_double_width(&self.block_manager.ghost_block);
-        self.view.draw_double_width(&self.block_manager.block);
+impl MainLoopRoot for Game {
+    type InputDataType = Vec<Event>;
 
-        // Next piece display
-        self.view
-            .draw(&Text::new(Vec2D::new(29, 9), "Next:", Modifier::None));
-        self.view
-            .draw_double_width(&self.block_manager.next_piece_display());
+    fn get_fps(&self) -> f32 {
+        tetris::FPS as f32
+    }
+
+    fn frame(&mut self, input_data: Option<Self::InputDataType>) {
+        // Handle Inputs
+        for event in input_data.into_iter().flatten() {
+            if let Event::Key(KeyEvent { code, kind, .. }) = event {
+                if self.screen == Screen::Playing {
+                    self.handle_key(code, kind);
+                } else if kind == KeyEventKind::Press {
+                    self.handle_screen_key(code);
+                }
+            }
+        }
 
-        // Held piece display
-        if let Some(held_piece) = self.block_manager.held_piece_display() {
-            self.view
-                .draw(&Text::new(Vec2D::new(29, 1), "Hold", Modifier::None));
-            self.view.draw_double_width(&held_piece);
+        let alerts = if self.screen == Screen::Playing {
+            self.play_frame()
         } else {
-            self.view.draw(&Sprite::new(
-                Vec2D::new(26, 0),
-                &self.controls_help_text,
-                Modifier::None,
-            ));
+            vec![]
+        };
+        if self.spectators.is_some() {
+            self.spectate(alerts);
         }
+    }
 
-        // Score display
-        self.view.draw(&Text::new(
-            Vec2D::new(26, 7),
-            &format!("Score: {}", self.score),
-            Modifier::None,
-        ));
+    fn render_frame(&mut self) {
+        self.view.clear();
 
-        // Alerts display
-        self.view.draw(&self.alert_display);
-        self.alert_display.frame();
+        match self.screen_text() {
+            Some(text) => screens::draw_centred_text(&mut self.view, &text),
+            None => self.draw_game(),
+        }
 
-        execute!(stdout(), MoveTo(0, 0)).unwrap();
-        execute!(stdout(), Clear(ClearType::FromCursorDown)).unwrap();
+        execute!(stdout(), MoveTo(0, 0)).expect("Failed to move cursor");
+        execute!(stdout(), Clear(ClearType::FromCursorDown)).expect("Failed to clear screen");
         self.view
             .display_render()
             .expect("Failed to print render to screen");
@@ -243,23 +407,6 @@ impl MainLoopRoot for Game {
         fps: f32,
         elapsed: std::time::Duration,
     ) -> (bool, Option<Self::InputDataType>) {
-        let frame_duration = std::time::Duration::from_secs_f32(1.0 / fps);
-        let sleep_duration = frame_duration
-            .checked_sub(elapsed)
-            .unwrap_or(std::time::Duration::ZERO);
-
-        if sleep_duration > std::time::Duration::ZERO {
-            std::thread::sleep(sleep_duration);
-        }
-
-        if let Ok(event) = crossterm::event::poll(std::time::Duration::from_millis(0)) {
-            if event {
-                if let Ok(input_event) = crossterm::event::read() {
-                    return (false, Some(input_event));
-                }
-            }
-        }
-
-        (false, None)
+        (false, Some(keyboard::sleep_and_read_events(fps, elapsed)))
     }
 }
@@ -0,0 +1,194 @@
+use std::sync::Arc;
+
+use gemini_engine::{
+    containers::PixelContainer,
+    core::{ColChar, Colour, Modifier, Vec2D},
+};
+use serde::{Deserialize, Serialize};
+use tetris::{
+    stack_effect::{StackEffect, StackView},
+    Block, Board, Engine,
+};
+use thiserror::Error;
+
+/// Everything needed to draw a board and its HUD, taken from its engine every frame. Boards are
+/// always drawn from one of these, so that spectators can draw a board sent to them exactly the
+/// way it's drawn on the machine it's played on
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct BoardState {
+    /// The size of the board in cells
+    pub width: usize,
+    pub height: usize,
+    /// The number of characters each cell is drawn as, across and down
+    pub scale: i64,
+    /// The placed cells that can be seen, after the stack effect
+    pub stack: Vec<Cell>,
+    pub active: Piece,
+    /// The cells the active piece would land on
+    pub ghost: Vec<[i64; 2]>,
+    /// The held piece, relative to where it's drawn
+    pub hold: Option<Piece>,
+    /// The upcoming pieces, starting with the next one to spawn, relative to where they're drawn
+    pub queue: Vec<Piece>,
+    /// The progress lines shown in the HUD, depending on the mode
+    pub hud: Vec<String>,
+    pub score: i64,
+    pub seed: u64,
+    /// The name of the randomizer the pieces are dealt by, shown next to the seed
+    pub randomizer: String,
+    /// The garbage lines waiting to rise
+    pub incoming_garbage: u32,
+    pub lines_sent: u32,
+    pub is_game_over: bool,
+}
+
+impl BoardState {
+    pub fn new(engine: &Engine) -> Self {
+        let board = &engine.board().stationary_blocks;
+        let shape_piece = |shape| Piece::new(&Block::new(Arc::clone(shape)));
+
+        Self {
+            width: board.width(),
+            height: board.height(),
+            scale: engine.scale(),
+            stack: engine
+                .visible_board()
+                .cells()
+                .map(|(pos, colour)| Cell {
+                    x: pos.x,
+                    y: pos.y,
+                    colour: Rgb::of(colour),
+                })
+                .collect(),
+            active: Piece::new(engine.active_block()),
+            ghost: positions(&engine.ghost_block().cells()),
+            hold: engine.held_piece().map(shape_piece),
+            queue: engine.queue().map(shape_piece).collect(),
+            hud: engine.mode().hud_lines(engine).into(),
+            score: engine.score(),
+            seed: engine.seed(),
+            randomizer: engine.randomizer().name().to_string(),
+            incoming_garbage: engine.incoming_garbage(),
+            lines_sent: engine.stats().lines_sent,
+            is_game_over: engine.is_game_over(),
+        }
+    }
+
+    /// The board with every cell that can be seen filled in, ready to draw the same way as an
+    /// engine's board
+    pub fn stack(&self) -> Board {
+        let mut board = Board::new(self.width, self.height);
+        for cell in &self.stack {
+            board.fill(Vec2D::new(cell.x, cell.y), Rgb::to_col_char(cell.colour));
+        }
+        board
+    }
+
+    /// Draws the board filled in by [`BoardState::stack`] with its walls
+    pub const fn stack_view<'a>(&self, board: &'a Board) -> StackView<'a> {
+        StackView {
+            board,
+            // The stack effect was already applied to the cells that were kept
+            effect: StackEffect::Visible,
+            frame: 0,
+            flash: false,
+            scale: self.scale,
+        }
+    }
+
+    pub fn ghost(&self) -> PixelContainer {
+        PixelContainer::from((&vectors(&self.ghost)[..], ColChar::BACKGROUND))
+    }
+}
+
+/// A placed cell of the board
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Cell {
+    pub x: i64,
+    pub y: i64,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub colour: Option<Rgb>,
+}
+
+/// The cells of a piece and its colour
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Piece {
+    pub colour: Rgb,
+    pub cells: Vec<[i64; 2]>,
+}
+
+impl Piece {
+    fn new(block: &Block) -> Self {
+        Self {
+            colour: Rgb(block.shape.colour),
+            cells: positions(&block.cells()),
+        }
+    }
+
+    /// The piece moved across by `offset`, ready to draw
+    pub fn at(&self, offset: Vec2D) -> PixelContainer {
+        let cells: Vec<Vec2D> = vectors(&self.cells)
+            .into_iter()
+            .map(|cell| cell + offset)
+            .collect();
+        PixelContainer::from((&cells[..], ColChar::SOLID.with_colour(self.colour.0)))
+    }
+}
+
+/// A colour, written as `#rrggbb`
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(into = "String", try_from = "String")]
+pub struct Rgb(pub Colour);
+
+impl Rgb {
+    /// The colour of a cell, if it has one
+    const fn of(colour: ColChar) -> Option<Self> {
+        match colour.modifier {
+            Modifier::Colour(colour) => Some(Self(colour)),
+            _ => None,
+        }
+    }
+
+    const fn to_col_char(colour: Option<Self>) -> ColChar {
+        match colour {
+            Some(Self(colour)) => ColChar::SOLID.with_colour(colour),
+            None => ColChar::SOLID,
+        }
+    }
+}
+
+impl From<Rgb> for String {
+    fn from(Rgb(colour): Rgb) -> Self {
+        format!("#{:02x}{:02x}{:02x}", colour.r, colour.g, colour.b)
+    }
+}
+
+impl TryFrom<String> for Rgb {
+    type Error = InvalidColour;
+
+    fn try_from(hex: String) -> Result<Self, Self::Error> {
+        let channel = |i: usize| {
+            hex.get(i..i + 2)
+                .and_then(|channel| u8::from_str_radix(channel, 16).ok())
+                .ok_or(InvalidColour)
+        };
+        if hex.len() != 7 || !hex.starts_with('#') {
+            return Err(InvalidColour);
+        }
+
+        Ok(Self(Colour::rgb(channel(1)?, channel(3)?, channel(5)?)))
+    }
+}
+
+/// Returned when a colour isn't written as `#rrggbb`
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Error)]
+#[error("colours should be written as #rrggbb")]
+pub struct InvalidColour;
+
+fn positions(cells: &[Vec2D]) -> Vec<[i64; 2]> {
+    cells.iter().map(|cell| [cell.x, cell.y]).collect()
+}
+
+fn vectors(cells: &[[i64; 2]]) -> Vec<Vec2D> {
+    cells.iter().map(|&[x, y]| Vec2D::new(x, y)).collect()
+}
@@ -0,0 +1,143 @@
+use std::{io::stdout, thread, time::Duration};
+
+use console_input::keypress::exit_raw_mode;
+use crossterm::{
+    event::{
+        self, Event, KeyCode, KeyboardEnhancementFlags, PopKeyboardEnhancementFlags,
+        PushKeyboardEnhancementFlags,
+    },
+    execute,
+    terminal::supports_keyboard_enhancement,
+};
+use tetris::Input;
+
+/// The keys one player controls their board with
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum KeyMap {
+    /// The arrow keys along with Z, X, A, C and Space, when there's only one player
+    Solo,
+    /// WASD with Q and E to rotate, for the player on the left
+    Left,
+    /// The arrow keys with comma and full stop to rotate, for the player on the right
+    Right,
+}
+
+impl KeyMap {
+    /// The key maps for a game with `players` players, from left to right
+    pub fn for_players(players: usize) -> Vec<Self> {
+        if players == 1 {
+            vec![Self::Solo]
+        } else {
+            [Self::Left, Self::Right]
+                .into_iter()
+                .take(players)
+                .collect()
+        }
+    }
+
+    /// The input `code` presses on this player's board, if any
+    pub const fn input(self, code: KeyCode) -> Option<Input> {
+        let input = match (self, code) {
+            (Self::Solo | Self::Right, KeyCode::Left) | (Self::Left, KeyCode::Char('a')) => {
+                Input::ShiftLeft
+            }
+            (Self::Solo | Self::Right, KeyCode::Right) | (Self::Left, KeyCode::Char('d')) => {
+                Input::ShiftRight
+            }
+            (Self::Solo, KeyCode::Char('z'))
+            | (Self::Left, KeyCode::Char('q'))
+            | (Self::Right, KeyCode::Char(',')) => Input::RotateAnticlockwise,
+            (Self::Solo, KeyCode::Up | KeyCode::Char('x'))
+            | (Self::Left, KeyCode::Char('e'))
+            | (Self::Right, KeyCode::Char('.')) => Input::RotateClockwise,
+            (Self::Solo, KeyCode::Char('a'))
+            | (Self::Left, KeyCode::Char('r'))
+            | (Self::Right, KeyCode::Char('/')) => Input::Rotate180,
+            (Self::Solo | Self::Right, KeyCode::Down) | (Self::Left, KeyCode::Char('s')) => {
+                Input::SoftDrop
+            }
+            (Self::Solo, KeyCode::Char(' '))
+            | (Self::Left, KeyCode::Char('w'))
+            | (Self::Right, KeyCode::Up) => Input::HardDrop,
+            (Self::Solo, KeyCode::Char('c'))
+            | (Self::Left, KeyCode::Char('f'))
+            | (Self::Right, KeyCode::Char('m')) => Input::Hold,
+            _ => return None,
+        };
+
+        Some(input)
+    }
+
+    /// The controls shown on the HUD until a piece is held
+    pub const fn help_text(self) -> &'static str {
+        match self {
+            Self::Solo => {
+                "Controls:
+C to hold
+Left/Right to shift
+Space hard | Down soft
+Z AC | Up/X C | A 180
+Esc to pause"
+            }
+            Self::Left => {
+                "Controls:
+F to hold
+A/D to shift
+W hard | S soft
+Q AC | E C | R 180
+Esc to pause"
+            }
+            Self::Right => {
+                "Controls:
+M to hold
+Left/Right to shift
+Up hard | Down soft
+, AC | . C | / 180
+Esc to pause"
+            }
+        }
+    }
+}
+
+/// Ask the terminal to report key releases, so that held keys can be repeated by the engine
+/// instead of the OS. Returns false if the terminal doesn't support this, in which case every key
+/// press has to be treated as a tap
+pub fn enable_key_release_events() -> bool {
+    supports_keyboard_enhancement().unwrap_or(false)
+        && execute!(
+            stdout(),
+            PushKeyboardEnhancementFlags(KeyboardEnhancementFlags::REPORT_EVENT_TYPES)
+        )
+        .is_ok()
+}
+
+/// Sleep for the rest of a frame that has taken `elapsed` so far, then read every event that came
+/// in during it
+pub fn sleep_and_read_events(fps: f32, elapsed: Duration) -> Vec<Event> {
+    let frame_duration = Duration::from_secs_f32(1.0 / fps);
+    let sleep_duration = frame_duration
+        .checked_sub(elapsed)
+        .unwrap_or(Duration::ZERO);
+
+    if sleep_duration > Duration::ZERO {
+        thread::sleep(sleep_duration);
+    }
+
+    // Collect every event that came in during the frame, so that no presses or releases are
+    // missed
+    let mut events = vec![];
+    while event::poll(Duration::ZERO).unwrap_or(false) {
+        match event::read() {
+            Ok(event) => events.push(event),
+            Err(_) => break,
+        }
+    }
+
+    events
+}
+
+/// Restore the terminal and exit the process
+pub fn exit() {
+    let _ = execute!(stdout(), PopKeyboardEnhancementFlags);
+    exit_raw_mode();
+}
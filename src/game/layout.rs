@@ -0,0 +1,409 @@
+use gemini_engine::{
+    ascii::{Sprite, Text},
+    containers::PixelContainer,
+    core::{ColChar, Modifier, Vec2D},
+    view::View,
+};
+use serde::{Deserialize, Serialize};
+use tetris::{Board, Settings};
+use thiserror::Error;
+
+use super::{alerts::AlertDisplay, board_state::BoardState, versus};
+
+/// The number of columns taken up by the HUD to the right of the board
+const HUD_WIDTH: usize = 24;
+/// The number of rows needed to fit the HUD with the standard pieces
+const HUD_HEIGHT: usize = 21;
+/// The row of the first piece in the queue
+const QUEUE_Y: i64 = 12;
+/// The most columns or rows a layout received from somewhere else can take up, and the furthest a
+/// cell can be from its board
+const MAX_VIEW_SIZE: i64 = 1024;
+
+/// Where every board and HUD goes on screen. Worked out from the settings, and sent to spectators
+/// so they can draw the game the same way without knowing the settings
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Layout {
+    /// The number of boards drawn with a HUD, one for each player
+    pub players: usize,
+    /// The number of bot boards drawn right of the players'
+    pub bots: usize,
+    /// The column the HUD starts at, just right of the board
+    hud_x: i64,
+    /// The number of columns each player's board and HUD take up together
+    player_width: i64,
+    /// The column of the double width canvas the first bot's board starts at, right of the last
+    /// player's HUD
+    bots_x: i64,
+    /// The number of columns each board takes up, including the gap after it
+    board_spacing: i64,
+    /// The row just below the boards
+    board_bottom: i64,
+    /// The number of rows between the pieces in the queue, enough to fit the tallest piece
+    queue_spacing: i64,
+    hud_height: i64,
+    /// Where alerts are shown on the first player's board
+    alert_pos: (i64, i64),
+}
+
+impl Layout {
+    pub fn new(settings: &Settings, players: usize, bots: usize) -> Self {
+        // The board is drawn at double width, with a wall on either side. In Big mode the field
+        // can be a column or row smaller than asked for, to fit whole double size cells
+        let board_width = settings.field_width() * settings.scale();
+        let board_height = settings.field_height() * settings.scale();
+        let board_view_width = (board_width + 2) * 2;
+        let queue_spacing = (settings.piece_set.max_spawn_height() as i64 + 1).max(3);
+        let hud_height = (QUEUE_Y as usize - 1
+            + settings.piece_preview_count * queue_spacing as usize)
+            .max(HUD_HEIGHT);
+        let player_width = board_view_width + 2 + HUD_WIDTH;
+
+        Self {
+            players,
+            bots,
+            hud_x: board_view_width as i64 + 2,
+            player_width: player_width as i64,
+            bots_x: (players * player_width) as i64 / 2,
+            board_spacing: board_view_width as i64 + 2,
+            board_bottom: board_height as i64 + 1,
+            queue_spacing,
+            hud_height: hud_height as i64,
+            alert_pos: (board_view_width as i64 / 2, board_height as i64 * 7 / 20),
+        }
+    }
+
+    /// A view big enough to fit everything
+    pub fn view(&self) -> View {
+        let (width, height) = self.size().expect("Layout is too big to draw");
+        View::new(width as usize, height as usize, ColChar::EMPTY)
+    }
+
+    /// The number of columns and rows everything takes up, if it can be worked out at all
+    fn size(&self) -> Option<(i64, i64)> {
+        let players = i64::try_from(self.players).ok()?;
+        let bots = i64::try_from(self.bots).ok()?;
+        let width = players
+            .checked_mul(self.player_width)?
+            .checked_add(bots.checked_mul(self.board_spacing)?)?;
+        // Each bot's board is labelled on the row below it
+        let label_height = i64::from(self.bots > 0);
+        let height = self
+            .board_bottom
+            .checked_add(label_height)?
+            .max(self.hud_height);
+
+        Some((width, height))
+    }
+
+    /// Make sure a layout received from somewhere else can be drawn
+    ///
+    /// # Errors
+    /// Returns [`InvalidLayout::TooBig`] if there are no players or it won't fit on screen
+    pub fn check(&self) -> Result<(), InvalidLayout> {
+        let positions = [
+            self.hud_x,
+            self.player_width,
+            self.bots_x,
+            self.board_spacing,
+            self.board_bottom,
+            self.queue_spacing,
+            self.hud_height,
+            self.alert_pos.0,
+            self.alert_pos.1,
+        ];
+        let fits = |size| (0..=MAX_VIEW_SIZE).contains(&size);
+        let size_fits = self
+            .size()
+            .is_some_and(|(width, height)| fits(width) && fits(height));
+        if self.players == 0 || !size_fits || !positions.into_iter().all(fits) {
+            return Err(InvalidLayout::TooBig);
+        }
+
+        Ok(())
+    }
+
+    /// Make sure the boards received from somewhere else, one for each player and bot, can be
+    /// drawn with this layout
+    ///
+    /// # Errors
+    /// Returns an error if there are too few or too many boards, or one of them doesn't fit
+    pub fn check_boards<'a>(
+        &self,
+        players: impl ExactSizeIterator<Item = &'a BoardState>,
+        bots: &'a [BoardState],
+    ) -> Result<(), InvalidLayout> {
+        for (expected, received) in [(self.players, players.len()), (self.bots, bots.len())] {
+            if expected != received {
+                return Err(InvalidLayout::BoardCount { expected, received });
+            }
+        }
+
+        players
+            .chain(bots)
+            .try_for_each(|state| self.check_board(state))
+    }
+
+    fn check_board(&self, state: &BoardState) -> Result<(), InvalidLayout> {
+        // Only Big mode scales the board, to double size
+        if !(1..=Board::MAX_WIDTH).contains(&state.width)
+            || !(1..=MAX_VIEW_SIZE as usize).contains(&state.height)
+            || !(1..=2).contains(&state.scale)
+        {
+            return Err(InvalidLayout::BoardSize);
+        }
+        let (width, height) = (state.width as i64, state.height as i64);
+        if (width * state.scale + 2) * 2 + 2 != self.board_spacing
+            || height * state.scale + 1 != self.board_bottom
+        {
+            return Err(InvalidLayout::BoardSize);
+        }
+
+        let max_cells = state.width * state.height;
+        // Columns are numbered from 1, right of the left wall
+        let on_board = |x, y| (1..=width).contains(&x) && (0..height).contains(&y);
+        let near_board = |&[x, y]: &[i64; 2]| {
+            (-MAX_VIEW_SIZE..=MAX_VIEW_SIZE).contains(&x)
+                && (-MAX_VIEW_SIZE..=MAX_VIEW_SIZE).contains(&y)
+        };
+        let mut pieces = std::iter::once(&state.active)
+            .chain(&state.hold)
+            .chain(&state.queue);
+        if state.stack.len() > max_cells
+            || state.ghost.len() > max_cells
+            || state.queue.len() > max_cells
+            || !state.stack.iter().all(|cell| on_board(cell.x, cell.y))
+            || !state.ghost.iter().all(near_board)
+            || !pieces
+                .all(|piece| piece.cells.len() <= max_cells && piece.cells.iter().all(near_board))
+        {
+            return Err(InvalidLayout::CellOutOfBounds);
+        }
+
+        Ok(())
+    }
+
+    /// Where the `i`th player's alerts are shown
+    pub const fn alert_display(&self, i: usize) -> AlertDisplay {
+        let (x, y) = self.alert_pos;
+        AlertDisplay::new(Vec2D::new(x + i as i64 * self.player_width, y))
+    }
+
+    /// Draw the board and HUD of the `i`th player, each one right of the last. `help_text` is
+    /// shown where the held piece goes until there is one
+    pub fn draw_player(&self, view: &mut View, i: usize, state: &BoardState, help_text: &str) {
+        let x = i as i64 * self.player_width;
+        let hud_x = x + self.hud_x;
+
+        versus::draw_board(view, state, Vec2D::new(x / 2, 0));
+
+        // Next piece display
+        view.draw(&Text::new(
+            Vec2D::new(hud_x + 3, 9),
+            "Next:",
+            Modifier::None,
+        ));
+        view.draw_double_width(&self.next_piece_display(state, hud_x));
+
+        // Held piece display
+        if let Some(held_piece) = &state.hold {
+            view.draw(&Text::new(Vec2D::new(hud_x + 3, 1), "Hold", Modifier::None));
+            view.draw_double_width(&held_piece.at(Self::hud_double_width_pos(hud_x, 4)));
+        } else {
+            view.draw(&Sprite::new(
+                Vec2D::new(hud_x, 0),
+                help_text,
+                Modifier::None,
+            ));
+        }
+
+        // Progress display, depending on the mode
+        for (row, line) in state.hud.iter().enumerate() {
+            view.draw(&Text::new(
+                Vec2D::new(hud_x, 6 + row as i64),
+                line,
+                Modifier::None,
+            ));
+        }
+        view.draw(&Text::new(
+            Vec2D::new(hud_x, self.hud_height - 1),
+            &format!("Seed: {} {}", state.seed, state.randomizer),
+            Modifier::None,
+        ));
+    }
+
+    /// Draw every bot's board right of the players' boards
+    pub fn draw_bots(&self, view: &mut View, bots: &[BoardState]) {
+        versus::draw_bots(
+            view,
+            bots,
+            self.bots_x,
+            self.board_spacing / 2,
+            self.board_bottom,
+        );
+    }
+
+    /// The position of a HUD element on the double width canvas, for the HUD at column `hud_x`
+    const fn hud_double_width_pos(hud_x: i64, y: i64) -> Vec2D {
+        Vec2D::new(hud_x / 2 + 2, y)
+    }
+
+    fn next_piece_display(&self, state: &BoardState, hud_x: i64) -> PixelContainer {
+        let mut container = PixelContainer::new();
+        for (i, piece) in state.queue.iter().enumerate() {
+            let pos = Self::hud_double_width_pos(hud_x, QUEUE_Y + i as i64 * self.queue_spacing);
+            container.draw(&piece.at(pos));
+        }
+
+        container
+    }
+}
+
+/// Returned when a layout, or the boards to draw with it, came from somewhere else and can't be
+/// drawn
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Error)]
+pub enum InvalidLayout {
+    #[error("the layout is too big to draw")]
+    TooBig,
+    #[error("expected {expected} boards but received {received}")]
+    BoardCount { expected: usize, received: usize },
+    #[error("a board doesn't fit the layout")]
+    BoardSize,
+    #[error("a board has cells outside it")]
+    CellOutOfBounds,
+}
+
+#[cfg(test)]
+mod tests {
+    use tetris::Engine;
+
+    use super::*;
+    use crate::game::board_state::Cell;
+
+    /// A layout for one player and one bot with `settings`, and a board for each of them
+    fn game(settings: &Settings) -> (Layout, Vec<BoardState>) {
+        let layout = Layout::new(settings, 1, 1);
+        let state = BoardState::new(&Engine::new(settings, 0));
+        (layout, vec![state.clone(), state])
+    }
+
+    fn check(layout: &Layout, states: &[BoardState]) -> Result<(), InvalidLayout> {
+        layout.check_boards(states[..1].iter(), &states[1..])
+    }
+
+    #[test]
+    fn layouts_from_settings_pass_their_own_checks() {
+        for settings in [
+            Settings::default(),
+            Settings {
+                big: true,
+                ..Settings::default()
+            },
+            Settings {
+                board_width: Board::MAX_WIDTH,
+                piece_preview_count: 6,
+                ..Settings::default()
+            },
+        ] {
+            let (layout, states) = game(&settings);
+            assert_eq!(layout.check(), Ok(()));
+            assert_eq!(check(&layout, &states), Ok(()));
+        }
+    }
+
+    #[test]
+    fn the_wrong_number_of_boards_is_rejected() {
+        let (layout, states) = game(&Settings::default());
+        assert_eq!(
+            layout.check_boards(states.iter(), &states[..1]),
+            Err(InvalidLayout::BoardCount {
+                expected: 1,
+                received: 2
+            })
+        );
+        assert_eq!(
+            layout.check_boards(states[..1].iter(), &[]),
+            Err(InvalidLayout::BoardCount {
+                expected: 1,
+                received: 0
+            })
+        );
+    }
+
+    #[test]
+    fn boards_that_dont_fit_the_layout_are_rejected() {
+        let (layout, mut states) = game(&Settings::default());
+        states[1].scale = 2;
+        assert_eq!(check(&layout, &states), Err(InvalidLayout::BoardSize));
+
+        let (layout, mut states) = game(&Settings::default());
+        states[0].width += 1;
+        assert_eq!(check(&layout, &states), Err(InvalidLayout::BoardSize));
+
+        let (layout, mut states) = game(&Settings::default());
+        states[0].height = usize::MAX;
+        assert_eq!(check(&layout, &states), Err(InvalidLayout::BoardSize));
+    }
+
+    #[test]
+    fn stack_cells_off_the_board_are_rejected() {
+        let (layout, states) = game(&Settings::default());
+        for (x, y) in [(0, 0), (11, 0), (1, -1), (1, 20)] {
+            let mut states = states.clone();
+            states[1].stack.push(Cell { x, y, colour: None });
+            assert_eq!(check(&layout, &states), Err(InvalidLayout::CellOutOfBounds));
+        }
+
+        // Pieces can be drawn outside the board, but not anywhere at all
+        let mut states = states;
+        states[0].active.cells.push([-1, -1]);
+        assert_eq!(check(&layout, &states), Ok(()));
+        states[0].active.cells.push([MAX_VIEW_SIZE + 1, 0]);
+        assert_eq!(check(&layout, &states), Err(InvalidLayout::CellOutOfBounds));
+    }
+
+    #[test]
+    fn oversized_layouts_are_rejected() {
+        let layout = Layout::new(&Settings::default(), 1, 0);
+        assert_eq!(
+            Layout {
+                players: 0,
+                ..layout
+            }
+            .check(),
+            Err(InvalidLayout::TooBig)
+        );
+        assert_eq!(
+            Layout {
+                players: usize::MAX,
+                ..layout
+            }
+            .check(),
+            Err(InvalidLayout::TooBig)
+        );
+        assert_eq!(
+            Layout {
+                bots: MAX_VIEW_SIZE as usize,
+                ..layout
+            }
+            .check(),
+            Err(InvalidLayout::TooBig)
+        );
+        assert_eq!(
+            Layout {
+                hud_height: -1,
+                ..layout
+            }
+            .check(),
+            Err(InvalidLayout::TooBig)
+        );
+        assert_eq!(
+            Layout {
+                alert_pos: (0, i64::MAX),
+                ..layout
+            }
+            .check(),
+            Err(InvalidLayout::TooBig)
+        );
+    }
+}
@@ -0,0 +1,327 @@
+use std::{cmp::Ordering, collections::VecDeque};
+
+use tetris::{game_mode::GameResult, Engine};
+
+use crate::net::{Connection, InputEvent, Message};
+
+/// The most frames the opponent's board is played ahead of the last frame received from them.
+/// After that it waits for their frames to catch up
+const MAX_PREDICTION_FRAMES: u64 = 30;
+
+/// How an online game ended for the player
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Outcome {
+    Won,
+    Lost,
+    Draw,
+}
+
+/// A game against someone on another machine. Their board is a copy kept in sync by playing out
+/// the frames they send.
+///
+/// Their frames take a while to arrive, so the board shown for them is a prediction: the last
+/// board their frames were played out on, carried forward as if they kept holding whatever they
+/// were holding. When more of their frames arrive it's rolled back to that board and predicted
+/// again from there. The player's own inputs can be held back a few frames too, so that they
+/// arrive in time to be played on the same frame on the other machine.
+///
+/// The winner is whoever finished first, going by the frame each board finished on. Both machines
+/// play out both boards the same way, so they always agree on who that was, however late the
+/// frames arrive
+pub struct Online {
+    connection: Connection,
+    /// The inputs made on the player's board this frame
+    inputs: Vec<InputEvent>,
+    /// The inputs and garbage sent for frames that haven't been played on the player's board yet,
+    /// oldest first
+    pending: VecDeque<(Vec<InputEvent>, u32)>,
+    /// The number of frames played on the player's board so far
+    frame: u64,
+    /// The opponent's board as of the last frame received from them
+    confirmed: Engine,
+    /// The number of frames played on the confirmed board
+    confirmed_frames: u64,
+    /// The number of frames played on the predicted board that's shown
+    predicted_frames: u64,
+    /// The frame the player's board finished on
+    finished_on: Option<u64>,
+    /// The frame the confirmed board finished on
+    opponent_finished_on: Option<u64>,
+    outcome: Option<Outcome>,
+}
+
+impl Online {
+    /// Start a game over `connection`, with the opponent's board starting out as `opponent`.
+    /// Inputs are held back for `input_delay` frames
+    pub fn new(connection: Connection, opponent: &Engine, input_delay: u32) -> Self {
+        let mut online = Self {
+            connection,
+            inputs: vec![],
+            pending: VecDeque::new(),
+            frame: 0,
+            confirmed: opponent.clone(),
+            confirmed_frames: 0,
+            predicted_frames: 0,
+            finished_on: None,
+            opponent_finished_on: None,
+            outcome: None,
+        };
+        // Nothing can happen in the frames before the first inputs come through
+        for _ in 0..input_delay {
+            online.send_frame(vec![], 0);
+        }
+        online
+    }
+
+    /// Note down an input made on the player's board. It's played once it's due
+    pub fn record(&mut self, event: InputEvent) {
+        self.inputs.push(event);
+    }
+
+    /// Play out the frames that have arrived on the opponent's board, queueing the garbage it
+    /// sends on the player's board. Then send the player's frame and play the inputs that are due
+    /// on the player's board, and predict the opponent's board up to the same frame. Returns the
+    /// alerts from the opponent's frames that arrived. Call once per frame before ticking the
+    /// player's board, and for as long as there's no [`Online::outcome`]
+    pub fn frame(&mut self, player: &mut Engine, opponent: &mut Engine) -> Vec<String> {
+        if self.finished_on.is_none() && player.is_game_over() {
+            self.finished_on = Some(self.frame);
+        }
+
+        let mut garbage = 0;
+        let mut received = false;
+        for message in self.connection.receive() {
+            let Message::Frame {
+                inputs,
+                garbage: opponent_garbage,
+                ..
+            } = message
+            else {
+                continue;
+            };
+
+            for event in inputs {
+                event.play_on(&mut self.confirmed);
+            }
+            self.confirmed.receive_garbage(opponent_garbage);
+            self.confirmed.tick();
+            self.confirmed_frames += 1;
+            if self.opponent_finished_on.is_none() && self.confirmed.is_game_over() {
+                self.opponent_finished_on = Some(self.confirmed_frames);
+            }
+            garbage += self.confirmed.take_attack();
+            received = true;
+        }
+        if self.outcome.is_none() {
+            self.outcome = self.decide(player);
+        }
+        let alerts = self.confirmed.take_alerts();
+        // The player's attacks were already played out on the opponent's copy of this board
+        player.take_attack();
+
+        let inputs = std::mem::take(&mut self.inputs);
+        self.send_frame(inputs, garbage);
+        if let Some((inputs, garbage)) = self.pending.pop_front() {
+            for event in inputs {
+                event.play_on(player);
+            }
+            player.receive_garbage(garbage);
+        }
+        self.frame += 1;
+
+        if received {
+            self.roll_back(opponent);
+        }
+        let target = self
+            .frame
+            .min(self.confirmed_frames + MAX_PREDICTION_FRAMES);
+        while self.predicted_frames < target {
+            opponent.tick();
+            self.predicted_frames += 1;
+        }
+        // Only what really happened is shown
+        opponent.take_alerts();
+        // The game is only over once it's over on the confirmed board
+        if opponent.result().is_some() && self.confirmed.result().is_none() {
+            self.roll_back(opponent);
+        }
+        alerts
+    }
+
+    /// How the game ended for the player, once both boards have been played far enough to tell
+    pub const fn outcome(&self) -> Option<Outcome> {
+        self.outcome
+    }
+
+    /// Work out who won, if it's known yet. Call before the player's frame is played
+    fn decide(&self, player: &Engine) -> Option<Outcome> {
+        let first = match (self.finished_on, self.opponent_finished_on) {
+            (Some(frame), Some(opponent_frame)) => frame.cmp(&opponent_frame),
+            // The other board was still going on the frame this one finished on
+            (Some(frame), None) if self.confirmed_frames >= frame => Ordering::Less,
+            (None, Some(opponent_frame)) if self.frame >= opponent_frame => Ordering::Greater,
+            _ => return None,
+        };
+
+        Some(match (first, player.result(), self.confirmed.result()) {
+            (Ordering::Less, Some(GameResult::Completed), _)
+            | (Ordering::Greater, _, Some(GameResult::ToppedOut))
+            | (Ordering::Equal, Some(GameResult::Completed), Some(GameResult::ToppedOut)) => {
+                Outcome::Won
+            }
+            (Ordering::Less | Ordering::Greater, ..)
+            | (Ordering::Equal, Some(GameResult::ToppedOut), Some(GameResult::Completed)) => {
+                Outcome::Lost
+            }
+            // Finishing together, the higher score wins
+            (Ordering::Equal, Some(GameResult::Completed), Some(GameResult::Completed)) => {
+                match player.score().cmp(&self.confirmed.score()) {
+                    Ordering::Greater => Outcome::Won,
+                    Ordering::Less => Outcome::Lost,
+                    Ordering::Equal => Outcome::Draw,
+                }
+            }
+            (Ordering::Equal, ..) => Outcome::Draw,
+        })
+    }
+
+    /// Whether the other player is still connected
+    pub const fn is_connected(&self) -> bool {
+        self.connection.is_open()
+    }
+
+    /// Send the player's frame, to be played once every frame before it has been
+    fn send_frame(&mut self, inputs: Vec<InputEvent>, garbage: u32) {
+        // A failed send closes the connection, which ends the game
+        let _ = self.connection.send(&Message::Frame {
+            frame: self.frame + self.pending.len() as u64,
+            inputs: inputs.clone(),
+            garbage,
+        });
+        self.pending.push_back((inputs, garbage));
+    }
+
+    /// Throw away the prediction, going back to the last board the opponent's frames were played
+    /// out on
+    fn roll_back(&mut self, opponent: &mut Engine) {
+        opponent.clone_from(&self.confirmed);
+        self.predicted_frames = self.confirmed_frames;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::{net::TcpListener, thread, time::Duration};
+
+    use tetris::{Input, Settings};
+
+    use super::*;
+    use crate::game::board_state::BoardState;
+
+    const SEED: u64 = 99;
+
+    /// Two ends of a game hosted on a free port
+    fn connect(settings: &Settings) -> (Connection, Connection) {
+        let listener = TcpListener::bind("127.0.0.1:0").expect("a free port should be bound");
+        let addr = listener
+            .local_addr()
+            .expect("the listener should have an address");
+        let host_settings = settings.clone();
+        let host = thread::spawn(move || Connection::accept(&listener, &host_settings, SEED));
+        let (guest, _) = Connection::connect(addr, settings).expect("the guest should connect");
+        let host = host
+            .join()
+            .expect("the host shouldn't panic")
+            .expect("the host should start the game");
+        (host, guest)
+    }
+
+    /// One machine in an online game
+    struct Side {
+        online: Online,
+        player: Engine,
+        opponent: Engine,
+    }
+
+    impl Side {
+        fn new(connection: Connection, input_delay: u32) -> Self {
+            let settings = Settings::default();
+            let opponent = Engine::new(&settings, SEED);
+            Self {
+                online: Online::new(connection, &opponent, input_delay),
+                player: Engine::new(&settings, SEED),
+                opponent,
+            }
+        }
+
+        /// Play a frame the way the game does
+        fn play(&mut self, inputs: &[InputEvent]) {
+            for &event in inputs {
+                self.online.record(event);
+            }
+            self.online.frame(&mut self.player, &mut self.opponent);
+            self.player.tick();
+        }
+    }
+
+    #[test]
+    fn late_frames_replace_the_prediction() {
+        let (host, guest) = connect(&Settings::default());
+        let mut host = Side::new(host, 0);
+        let mut guest = Side::new(guest, 0);
+        let inputs = [
+            vec![InputEvent::Press(Input::ShiftLeft)],
+            vec![
+                InputEvent::Release(Input::ShiftLeft),
+                InputEvent::Press(Input::HardDrop),
+            ],
+            vec![],
+        ];
+
+        // Nothing has arrived from the host, so its board is predicted without any inputs
+        for _ in 0..10 {
+            guest.play(&[]);
+        }
+        assert!(guest.opponent.board().stationary_blocks.is_empty());
+
+        for frame in &inputs {
+            host.play(frame);
+        }
+        for _ in 0..1000 {
+            if guest.online.confirmed_frames == inputs.len() as u64 {
+                break;
+            }
+            thread::sleep(Duration::from_millis(1));
+            guest.play(&[]);
+        }
+        assert_eq!(guest.online.confirmed_frames, inputs.len() as u64);
+
+        // The board shown has to be the one the host's inputs really led to, predicted ahead
+        let mut expected = Engine::new(&Settings::default(), SEED);
+        for frame in &inputs {
+            for &event in frame {
+                event.play_on(&mut expected);
+            }
+            expected.tick();
+        }
+        for _ in inputs.len() as u64..guest.online.predicted_frames {
+            expected.tick();
+        }
+        assert!(!expected.board().stationary_blocks.is_empty());
+        assert_eq!(BoardState::new(&guest.opponent), BoardState::new(&expected));
+    }
+
+    #[test]
+    fn inputs_are_played_after_the_input_delay() {
+        const INPUT_DELAY: u32 = 3;
+        let (host, _guest) = connect(&Settings::default());
+        let mut host = Side::new(host, INPUT_DELAY);
+
+        host.play(&[InputEvent::Press(Input::HardDrop)]);
+        for _ in 0..INPUT_DELAY {
+            assert!(host.player.board().stationary_blocks.is_empty());
+            host.play(&[]);
+        }
+        assert!(!host.player.board().stationary_blocks.is_empty());
+    }
+}
@@ -0,0 +1,163 @@
+use gemini_engine::{
+    ascii::{Text, TextAlign},
+    core::{Modifier, Vec2D},
+    view::View,
+};
+use tetris::{format_time, game_mode::GameResult, Engine, GameMode};
+
+use super::online::Outcome;
+
+/// What the game is currently showing
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Screen {
+    Menu,
+    Playing,
+    GameOver,
+}
+
+/// The title screen, with the goal of the mode that will be played
+pub fn menu_text(mode: GameMode) -> String {
+    format!(
+        "TETRIS
+
+{}
+
+Enter to play
+Q to quit",
+        mode.description()
+    )
+}
+
+const GAME_OVER_OPTIONS_TEXT: &str = "R to retry this seed
+N to play a new seed
+M for the menu
+Q to quit";
+
+/// Draw each line of `text` centred in the middle of the view
+pub fn draw_centred_text(view: &mut View, text: &str) {
+    let line_count = text.lines().count() as i64;
+    let centre = Vec2D::new(view.width as i64 / 2, (view.height as i64 - line_count) / 2);
+
+    for (i, line) in text.lines().enumerate() {
+        view.draw(
+            &Text::new(centre + Vec2D::new(0, i as i64), line, Modifier::None)
+                .with_align(TextAlign::Centered),
+        );
+    }
+}
+
+/// The winner of a game between players on the same keyboard, with how each of them did
+pub fn versus_over_text(engines: &[&Engine]) -> String {
+    let title = engines
+        .iter()
+        .position(|engine| engine.result() == Some(GameResult::Completed))
+        .map_or_else(
+            || "GAME OVER".to_string(),
+            |i| format!("PLAYER {} WINS", i + 1),
+        );
+    let results: Vec<String> = engines
+        .iter()
+        .enumerate()
+        .map(|(i, engine)| {
+            format!(
+                "Player {}: Score {} | Lines {} | Sent {}",
+                i + 1,
+                engine.score(),
+                engine.lines_cleared(),
+                engine.stats().lines_sent,
+            )
+        })
+        .collect();
+
+    format!(
+        "{title}
+
+Mode: {}
+{}
+Time: {}
+Seed: {} | Randomizer: {}
+
+{GAME_OVER_OPTIONS_TEXT}",
+        engines[0].mode().name(),
+        results.join("\n"),
+        format_time(engines[0].stats().time()),
+        engines[0].seed(),
+        engines[0].randomizer().name(),
+    )
+}
+
+/// The result of a game against someone on another machine
+pub fn online_over_text(
+    player: &Engine,
+    opponent: &Engine,
+    outcome: Option<Outcome>,
+    is_connected: bool,
+) -> String {
+    let title = match outcome {
+        Some(Outcome::Won) => "YOU WIN",
+        Some(Outcome::Lost) => "YOU LOSE",
+        Some(Outcome::Draw) => "DRAW",
+        None if !is_connected => "CONNECTION LOST",
+        None => "GAME OVER",
+    };
+
+    format!(
+        "{title}
+
+Mode: {}
+You: Score {} | Lines {} | Sent {}
+Opponent: Score {} | Lines {} | Sent {}
+Time: {}
+Seed: {} | Randomizer: {}
+
+Q to quit",
+        player.mode().name(),
+        player.score(),
+        player.lines_cleared(),
+        player.stats().lines_sent,
+        opponent.score(),
+        opponent.lines_cleared(),
+        opponent.stats().lines_sent,
+        format_time(player.stats().time()),
+        player.seed(),
+        player.randomizer().name(),
+    )
+}
+
+/// A summary of a finished game, followed by the options for what to do next
+pub fn game_over_text(engine: &Engine) -> String {
+    let stats = engine.stats();
+    let [singles, doubles, triples, tetrises] = stats.line_clears;
+
+    let title = match (engine.result(), engine.mode()) {
+        (Some(GameResult::Completed), GameMode::Sprint) => "SPRINT COMPLETE",
+        (Some(GameResult::Completed), GameMode::Ultra) => "TIME UP",
+        (Some(GameResult::Completed), GameMode::Battle) => "YOU WIN",
+        (Some(GameResult::Completed), _) => "COMPLETE",
+        _ => "GAME OVER",
+    };
+
+    format!(
+        "{title}
+
+Mode: {}
+Score: {}
+Level: {} | Lines: {}
+Time: {} | Pieces: {}
+Singles: {singles} | Doubles: {doubles}
+Triples: {triples} | Tetrises: {tetrises}
+Perfect clears: {}
+Seed: {} | Randomizer: {}
+
+{GAME_OVER_OPTIONS_TEXT}",
+        engine.mode().name(),
+        engine.score(),
+        engine.level(),
+        engine.lines_cleared(),
+        format_time(stats.time()),
+        stats.pieces_placed,
+        stats.perfect_clears,
+        engine.seed(),
+        engine.randomizer().name(),
+    )
+}
@@ -0,0 +1,256 @@
+//! Letting other people watch a game, read only, over a local socket
+//!
+//! A game started with `--spectate ADDR` listens on a TCP address or a Unix socket, and streams
+//! what's on screen to everyone connected to it. Nothing is sent back, so spectators can come and
+//! go at any time without the game noticing. `--watch ADDR` connects to a game and draws it the
+//! same way it's drawn for the players.
+//!
+//! # Protocol
+//!
+//! Messages are JSON objects, one per line, tagged by their `type`:
+//!
+//! 1. On connecting, a spectator is sent `{"type":"start","layout":{...}}`, which says where
+//!    every board and HUD goes on screen.
+//! 2. Then, for every frame of the game being played, `{"type":"frame","players":[...],
+//!    "bots":[...]}` with the state of every board. Each board has its `width` and `height` in
+//!    cells, the `stack` of placed cells that can be seen (`{"x":1,"y":19,"colour":"#00ffff"}`),
+//!    the `active` piece and its `ghost`, the `hold` piece and `queue` of upcoming pieces, the
+//!    lines of the `hud`, its `score` and more. Players' boards also list the `alerts`, like
+//!    "Tetris!", that came up that frame.
+//! 3. For every frame the menu or game over screen is shown instead, `{"type":"screen","text":
+//!    "..."}` with the text on it.
+//!
+//! The stream ends when the game is closed.
+#[cfg(unix)]
+use std::{
+    fs,
+    os::unix::{
+        fs::FileTypeExt,
+        net::{UnixListener, UnixStream},
+    },
+};
+use std::{
+    io::{self, BufReader, Write},
+    net::{TcpListener, TcpStream},
+    sync::{
+        mpsc::{self, SyncSender},
+        Arc,
+    },
+    thread,
+};
+
+use serde::{Deserialize, Serialize};
+use tetris::FPS;
+use thiserror::Error;
+
+use super::{
+    board_state::BoardState,
+    layout::{InvalidLayout, Layout},
+};
+use crate::{
+    args::Endpoint,
+    net::{self, Incoming, NetError},
+};
+
+/// The number of lines a spectator can fall behind by, a second of frames, before they're
+/// disconnected
+const MAX_QUEUED_LINES: usize = FPS as usize;
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum Message {
+    /// Sent to every spectator as soon as they connect
+    Start { layout: Layout },
+    /// The boards of the game in progress
+    Frame {
+        players: Vec<PlayerState>,
+        bots: Vec<BoardState>,
+    },
+    /// The text of the menu or game over screen
+    Screen { text: String },
+}
+
+/// A player's board, along with the alerts that came up on it this frame
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct PlayerState {
+    #[serde(flatten)]
+    pub board: BoardState,
+    pub alerts: Vec<String>,
+}
+
+/// The spectators watching a game, and the socket more of them can connect to
+pub struct Spectators {
+    listener: Listener,
+    /// The lines still to be written to each spectator. Every spectator is written to on a thread
+    /// of their own, so a slow one can't hold up the game
+    streams: Vec<SyncSender<Arc<str>>>,
+}
+
+impl Spectators {
+    /// Start listening for spectators at `endpoint`
+    pub fn bind(endpoint: &Endpoint) -> io::Result<Self> {
+        Ok(Self {
+            listener: Listener::bind(endpoint)?,
+            streams: vec![],
+        })
+    }
+
+    /// Let in any spectators that have connected since the last frame, then send `message` to
+    /// everyone watching. Spectators who have left, or fallen too far behind, are dropped. Call
+    /// once per frame
+    pub fn frame(&mut self, layout: Layout, message: &Message) {
+        while let Some(stream) = self.listener.accept() {
+            let sender = watch_stream(stream);
+            if sender.try_send(line(&Message::Start { layout })).is_ok() {
+                self.streams.push(sender);
+            }
+        }
+
+        if self.streams.is_empty() {
+            return;
+        }
+        let line = line(message);
+        self.streams
+            .retain(|sender| sender.try_send(Arc::clone(&line)).is_ok());
+    }
+}
+
+/// Returned when a game can't be watched
+#[derive(Debug, Error)]
+pub enum WatchError {
+    #[error(transparent)]
+    Net(#[from] NetError),
+    #[error("received a game that can't be drawn: {0}")]
+    Invalid(#[from] InvalidLayout),
+}
+
+/// A game being watched
+pub struct Feed {
+    incoming: Incoming<Message>,
+    /// What every frame received is checked against
+    layout: Layout,
+}
+
+impl Feed {
+    /// Start watching the game being spectated at `endpoint`. Returns the feed along with the
+    /// layout to draw the game with
+    pub fn connect(endpoint: &Endpoint) -> Result<(Self, Layout), WatchError> {
+        match endpoint {
+            Endpoint::Tcp(addr) => {
+                Self::start(TcpStream::connect(addr.as_str()).map_err(NetError::Io)?)
+            }
+            #[cfg(unix)]
+            Endpoint::Unix(path) => Self::start(UnixStream::connect(path).map_err(NetError::Io)?),
+        }
+    }
+
+    fn start(stream: impl io::Read + Send + 'static) -> Result<(Self, Layout), WatchError> {
+        let mut reader = BufReader::new(stream);
+        let Message::Start { layout } = net::read_message(&mut reader)? else {
+            return Err(NetError::UnexpectedMessage.into());
+        };
+        layout.check()?;
+
+        Ok((
+            Self {
+                incoming: Incoming::start(reader),
+                layout,
+            },
+            layout,
+        ))
+    }
+
+    /// Every message received since the last call
+    ///
+    /// # Errors
+    /// Returns an error if a frame received can't be drawn with the layout
+    pub fn receive(&mut self) -> Result<Vec<Message>, InvalidLayout> {
+        let messages = self.incoming.receive();
+        for message in &messages {
+            if let Message::Frame { players, bots } = message {
+                self.layout
+                    .check_boards(players.iter().map(|player| &player.board), bots)?;
+            }
+        }
+
+        Ok(messages)
+    }
+
+    /// Whether the game is still being streamed
+    pub const fn is_open(&self) -> bool {
+        self.incoming.is_open()
+    }
+}
+
+/// A socket spectators connect to
+enum Listener {
+    Tcp(TcpListener),
+    #[cfg(unix)]
+    Unix(UnixListener),
+}
+
+impl Listener {
+    fn bind(endpoint: &Endpoint) -> io::Result<Self> {
+        let listener = match endpoint {
+            Endpoint::Tcp(addr) => Self::Tcp(TcpListener::bind(addr.as_str())?),
+            #[cfg(unix)]
+            Endpoint::Unix(path) => {
+                // A socket left behind by an earlier game would stop this one from listening
+                if fs::symlink_metadata(path).is_ok_and(|metadata| metadata.file_type().is_socket())
+                {
+                    fs::remove_file(path)?;
+                }
+                Self::Unix(UnixListener::bind(path)?)
+            }
+        };
+
+        // Spectators are let in between frames, without waiting for them
+        match &listener {
+            Self::Tcp(listener) => listener.set_nonblocking(true)?,
+            #[cfg(unix)]
+            Self::Unix(listener) => listener.set_nonblocking(true)?,
+        }
+        Ok(listener)
+    }
+
+    /// A spectator that has connected, if any
+    fn accept(&self) -> Option<Box<dyn Write + Send>> {
+        // Streams can inherit the listener's non-blocking mode on some platforms
+        match self {
+            Self::Tcp(listener) => {
+                let (stream, _) = listener.accept().ok()?;
+                stream.set_nonblocking(false).ok()?;
+                stream.set_nodelay(true).ok()?;
+                Some(Box::new(stream))
+            }
+            #[cfg(unix)]
+            Self::Unix(listener) => {
+                let (stream, _) = listener.accept().ok()?;
+                stream.set_nonblocking(false).ok()?;
+                Some(Box::new(stream))
+            }
+        }
+    }
+}
+
+/// Write every line sent to a spectator's stream, until they leave. At most [`MAX_QUEUED_LINES`]
+/// are kept waiting, so a spectator that stops reading can't fill up memory
+fn watch_stream(mut stream: Box<dyn Write + Send>) -> SyncSender<Arc<str>> {
+    let (sender, receiver) = mpsc::sync_channel::<Arc<str>>(MAX_QUEUED_LINES);
+    thread::spawn(move || {
+        for line in receiver {
+            if stream.write_all(line.as_bytes()).is_err() {
+                break;
+            }
+        }
+    });
+
+    sender
+}
+
+/// A message as a line of JSON, ready to send
+fn line(message: &Message) -> Arc<str> {
+    let mut line = serde_json::to_string(message).unwrap_or_default();
+    line.push('\n');
+    line.into()
+}
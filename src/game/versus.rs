@@ -0,0 +1,183 @@
+use gemini_engine::{
+    ascii::Text,
+    core::{CanDraw, Canvas, ColChar, Colour, Modifier, Vec2D},
+    view::View,
+};
+use tetris::{bot::Bot, scaling::Scaled, Engine, GameMode, Settings};
+
+use super::board_state::BoardState;
+
+/// The colour of the incoming garbage meter
+const GARBAGE_METER_COLOUR: ColChar = ColChar::SOLID.with_colour(Colour::rgb(220, 30, 30));
+
+/// The bot controlled boards the player is battling, if any
+pub struct Opponents {
+    boards: Vec<(Engine, Bot)>,
+}
+
+impl Opponents {
+    /// Set up the bots for a game with `settings`. There are none outside of a battle
+    pub fn new(settings: &Settings, seed: u64) -> Self {
+        let bots = if settings.mode == GameMode::Battle {
+            settings.bots
+        } else {
+            0
+        };
+
+        // Every board gets the same seed, so nobody is dealt better pieces
+        Self {
+            boards: (0..bots)
+                .map(|_| {
+                    (
+                        Engine::new(settings, seed),
+                        Bot::new(settings.bot_move_delay),
+                    )
+                })
+                .collect(),
+        }
+    }
+
+    pub fn len(&self) -> usize {
+        self.boards.len()
+    }
+
+    /// Let every bot play a frame, then pass the garbage sent since the last frame around. Each
+    /// board attacks the next one still standing, with the players' boards first and the last
+    /// bot's board attacking the first player. The last board standing wins
+    pub fn frame<'a>(&'a mut self, players: impl IntoIterator<Item = &'a mut Engine>) {
+        for (engine, bot) in &mut self.boards {
+            bot.tick(engine);
+            engine.tick();
+            // Nobody is there to read the bots' alerts
+            engine.take_alerts();
+        }
+
+        let mut engines: Vec<&mut Engine> = players
+            .into_iter()
+            .chain(self.boards.iter_mut().map(|(engine, _)| engine))
+            .collect();
+        let attacks: Vec<u32> = engines
+            .iter_mut()
+            .map(|engine| engine.take_attack())
+            .collect();
+        let standing: Vec<usize> = (0..engines.len())
+            .filter(|&i| !engines[i].is_game_over())
+            .collect();
+        for (n, &attacker) in standing.iter().enumerate() {
+            let target = standing[(n + 1) % standing.len()];
+            if target != attacker {
+                engines[target].receive_garbage(attacks[attacker]);
+            }
+        }
+
+        if let [winner] = standing[..] {
+            if engines.len() > 1 {
+                engines[winner].win();
+            }
+        }
+    }
+
+    /// The state of every bot's board, to draw
+    pub fn states(&self) -> Vec<BoardState> {
+        self.boards
+            .iter()
+            .map(|(engine, _)| BoardState::new(engine))
+            .collect()
+    }
+}
+
+/// Draw every bot's board side by side, starting at column `x` of the double width canvas. Each
+/// board takes up `spacing` columns, with its label on row `label_y`
+pub fn draw_bots(view: &mut View, bots: &[BoardState], x: i64, spacing: i64, label_y: i64) {
+    for (i, state) in bots.iter().enumerate() {
+        let offset = Vec2D::new(x + i as i64 * spacing, 0);
+        draw_board(view, state, offset);
+
+        let status = if state.is_game_over {
+            "KO".to_string()
+        } else {
+            format!("Sent: {}", state.lines_sent)
+        };
+        view.draw(&Text::new(
+            Vec2D::new(offset.x * 2 + 2, label_y),
+            &format!("Bot {} - {status}", i + 1),
+            Modifier::None,
+        ));
+    }
+}
+
+/// Draw the stack, the ghost and active pieces and the incoming garbage meter of a board, moved
+/// across by `offset` on the double width canvas
+pub fn draw_board(view: &mut View, state: &BoardState, offset: Vec2D) {
+    let scale = state.scale;
+    let stack = state.stack();
+    view.draw_double_width(&Offset {
+        element: &state.stack_view(&stack),
+        offset,
+    });
+    view.draw_double_width(&Offset {
+        element: &Scaled {
+            element: &state.ghost(),
+            scale,
+        },
+        offset,
+    });
+    view.draw_double_width(&Offset {
+        element: &Scaled {
+            element: &state.active.at(Vec2D::ZERO),
+            scale,
+        },
+        offset,
+    });
+
+    view.draw_double_width(&Offset {
+        element: &GarbageMeter {
+            lines: state.incoming_garbage,
+            // The gap right of the board's wall
+            x: state.width as i64 * scale + 2,
+            floor: state.height as i64 * scale,
+        },
+        offset,
+    });
+}
+
+/// A column of cells, one for every garbage line waiting to rise, growing up from `floor`
+struct GarbageMeter {
+    lines: u32,
+    x: i64,
+    floor: i64,
+}
+
+impl CanDraw for GarbageMeter {
+    fn draw_to(&self, canvas: &mut impl Canvas) {
+        for y in (0..self.floor).rev().take(self.lines as usize) {
+            canvas.plot(Vec2D::new(self.x, y), GARBAGE_METER_COLOUR);
+        }
+    }
+}
+
+/// Draws an element moved across the canvas by `offset`
+struct Offset<'a, E> {
+    element: &'a E,
+    offset: Vec2D,
+}
+
+impl<E: CanDraw> CanDraw for Offset<'_, E> {
+    fn draw_to(&self, canvas: &mut impl Canvas) {
+        self.element.draw_to(&mut OffsetCanvas {
+            canvas,
+            offset: self.offset,
+        });
+    }
+}
+
+struct OffsetCanvas<'a, C> {
+    canvas: &'a mut C,
+    offset: Vec2D,
+}
+
+impl<C: Canvas> Canvas for OffsetCanvas<'_, C> {
+    fn plot(&mut self, pos: Vec2D, c: ColChar) {
+        self.canvas.plot(pos + self.offset, c);
+    }
+}
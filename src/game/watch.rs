@@ -0,0 +1,147 @@
+use std::io::stdout;
+
+use crossterm::{
+    cursor::MoveTo,
+    event::{Event, KeyCode, KeyEvent, KeyEventKind},
+    execute,
+    terminal::{Clear, ClearType},
+};
+use gemini_engine::{gameloop::MainLoopRoot, view::View};
+
+use super::{
+    alerts::AlertDisplay,
+    board_state::BoardState,
+    keyboard,
+    layout::Layout,
+    screens,
+    spectate::{Feed, Message},
+};
+
+const WAITING_TEXT: &str = "Waiting for the game to start...
+
+Q to stop watching";
+const ENDED_TEXT: &str = "The game has ended
+
+Q to quit";
+
+/// What's being shown of the game being watched
+enum Shown {
+    Boards {
+        players: Vec<BoardState>,
+        bots: Vec<BoardState>,
+    },
+    Text(String),
+}
+
+/// Someone else's game, drawn read only from what it streams to spectators
+pub struct Watch {
+    view: View,
+    /// Dropped if the feed sends something that can't be drawn
+    feed: Option<Feed>,
+    layout: Layout,
+    shown: Shown,
+    alert_displays: Vec<AlertDisplay>,
+}
+
+impl Watch {
+    pub fn new(feed: Feed, layout: Layout) -> Self {
+        Self {
+            view: layout.view(),
+            feed: Some(feed),
+            layout,
+            shown: Shown::Text(WAITING_TEXT.to_string()),
+            alert_displays: (0..layout.players)
+                .map(|i| layout.alert_display(i))
+                .collect(),
+        }
+    }
+}
+
+impl MainLoopRoot for Watch {
+    type InputDataType = Vec<Event>;
+
+    fn get_fps(&self) -> f32 {
+        tetris::FPS as f32
+    }
+
+    fn frame(&mut self, input_data: Option<Self::InputDataType>) {
+        for event in input_data.into_iter().flatten() {
+            if let Event::Key(KeyEvent {
+                code: KeyCode::Char('q') | KeyCode::Esc,
+                kind: KeyEventKind::Press,
+                ..
+            }) = event
+            {
+                keyboard::exit();
+            }
+        }
+
+        let Some(feed) = &mut self.feed else {
+            return;
+        };
+        let messages = match feed.receive() {
+            Ok(messages) => messages,
+            Err(err) => {
+                self.shown = Shown::Text(format!("Stopped watching:\n{err}\n\nQ to quit"));
+                self.feed = None;
+                return;
+            }
+        };
+        for message in messages {
+            self.shown = match message {
+                Message::Frame { players, bots } => {
+                    let players = players
+                        .into_iter()
+                        .zip(&mut self.alert_displays)
+                        .map(|(player, alert_display)| {
+                            for alert in &player.alerts {
+                                alert_display.push(alert);
+                            }
+                            player.board
+                        })
+                        .collect();
+                    Shown::Boards { players, bots }
+                }
+                Message::Screen { text } => Shown::Text(text),
+                // Only ever sent first
+                Message::Start { .. } => continue,
+            };
+        }
+        if !feed.is_open() {
+            self.shown = Shown::Text(ENDED_TEXT.to_string());
+        }
+    }
+
+    fn render_frame(&mut self) {
+        self.view.clear();
+
+        match &self.shown {
+            Shown::Boards { players, bots } => {
+                for (i, (state, alert_display)) in
+                    players.iter().zip(&mut self.alert_displays).enumerate()
+                {
+                    self.layout
+                        .draw_player(&mut self.view, i, state, "Spectating");
+                    self.view.draw(alert_display);
+                    alert_display.frame();
+                }
+                self.layout.draw_bots(&mut self.view, bots);
+            }
+            Shown::Text(text) => screens::draw_centred_text(&mut self.view, text),
+        }
+
+        execute!(stdout(), MoveTo(0, 0)).expect("Failed to move cursor");
+        execute!(stdout(), Clear(ClearType::FromCursorDown)).expect("Failed to clear screen");
+        self.view
+            .display_render()
+            .expect("Failed to print render to screen");
+    }
+
+    fn sleep_and_get_input_data(
+        &self,
+        fps: f32,
+        elapsed: std::time::Duration,
+    ) -> (bool, Option<Self::InputDataType>) {
+        (false, Some(keyboard::sleep_and_read_events(fps, elapsed)))
+    }
+}
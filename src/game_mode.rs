@@ -0,0 +1,256 @@
+//! The rules for how a game is won or lost, and what the HUD shows while it's played
+use std::{str::FromStr, time::Duration};
+
+use crate::{stats::format_time, Engine};
+
+/// The number of lines that completes a game of Marathon, reached at the end of level 15
+pub const MARATHON_LINES: u32 = 150;
+/// The number of lines that completes a Sprint
+pub const SPRINT_LINES: u32 = 40;
+/// How long a game of Ultra lasts
+pub const ULTRA_TIME_LIMIT: Duration = Duration::from_secs(120);
+/// The most garbage rows on the board at once in a Cheese Race. More are added as they're cleared
+pub const CHEESE_RACE_VISIBLE_ROWS: usize = 10;
+/// The time before the first garbage row rises in Survival
+pub const SURVIVAL_FIRST_RISE: Duration = Duration::from_secs(8);
+/// The shortest time between garbage rows rising in Survival
+pub const SURVIVAL_FASTEST_RISE: Duration = Duration::from_secs(1);
+/// How much of the time between rows is left after each row rises in Survival
+const SURVIVAL_ACCELERATION: f64 = 0.92;
+
+/// How a finished game ended
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum GameResult {
+    /// The stack reached the top of the board
+    ToppedOut,
+    /// The mode's goal was reached, or its time ran out
+    Completed,
+}
+
+/// The goal of a game
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum GameMode {
+    /// Clear 150 lines while the levels speed up
+    #[default]
+    Marathon,
+    /// Clear 40 lines as fast as possible
+    Sprint,
+    /// Score as much as possible in two minutes
+    Ultra,
+    /// Play forever without gravity. Topping out clears the board instead of ending the game
+    Zen,
+    /// Dig through [`Settings::cheese_lines`](crate::Settings::cheese_lines) rows of garbage as
+    /// fast as possible
+    CheeseRace,
+    /// Survive for as long as possible while garbage rises from below, faster and faster
+    Survival,
+    /// Send garbage to bot controlled opponents until every one of them tops out
+    Battle,
+}
+
+impl GameMode {
+    pub const ALL: [Self; 7] = [
+        Self::Marathon,
+        Self::Sprint,
+        Self::Ultra,
+        Self::Zen,
+        Self::CheeseRace,
+        Self::Survival,
+        Self::Battle,
+    ];
+
+    /// The name the mode is selected by
+    #[must_use]
+    pub const fn name(self) -> &'static str {
+        match self {
+            Self::Marathon => "marathon",
+            Self::Sprint => "sprint",
+            Self::Ultra => "ultra",
+            Self::Zen => "zen",
+            Self::CheeseRace => "cheese",
+            Self::Survival => "survival",
+            Self::Battle => "battle",
+        }
+    }
+
+    /// A one line summary of the mode's goal, shown on the menu
+    #[must_use]
+    pub const fn description(self) -> &'static str {
+        match self {
+            Self::Marathon => "Marathon: clear 150 lines",
+            Self::Sprint => "Sprint: clear 40 lines as fast as you can",
+            Self::Ultra => "Ultra: score as much as you can in 2 minutes",
+            Self::Zen => "Zen: no gravity and no game over",
+            Self::CheeseRace => "Cheese Race: dig through the garbage as fast as you can",
+            Self::Survival => "Survival: hold out against the rising garbage",
+            Self::Battle => "Battle: bury the bots in garbage to be the last one standing",
+        }
+    }
+
+    /// The number of lines that completes the game, if any
+    #[must_use]
+    pub const fn line_goal(self) -> Option<u32> {
+        match self {
+            Self::Marathon => Some(MARATHON_LINES),
+            Self::Sprint => Some(SPRINT_LINES),
+            Self::Ultra | Self::Zen | Self::CheeseRace | Self::Survival | Self::Battle => None,
+        }
+    }
+
+    /// How long the game lasts, if it's timed
+    #[must_use]
+    pub const fn time_limit(self) -> Option<Duration> {
+        match self {
+            Self::Ultra => Some(ULTRA_TIME_LIMIT),
+            Self::Marathon
+            | Self::Sprint
+            | Self::Zen
+            | Self::CheeseRace
+            | Self::Survival
+            | Self::Battle => None,
+        }
+    }
+
+    /// Whether the game ends when the stack reaches the top of the board
+    #[must_use]
+    pub const fn can_top_out(self) -> bool {
+        !matches!(self, Self::Zen)
+    }
+
+    /// Whether pieces fall on their own
+    #[must_use]
+    pub const fn has_gravity(self) -> bool {
+        !matches!(self, Self::Zen)
+    }
+
+    /// Whether the board starts full of garbage that has to be dug through
+    #[must_use]
+    pub const fn has_cheese(self) -> bool {
+        matches!(self, Self::CheeseRace)
+    }
+
+    /// The number of garbage rows kept on a board `board_height` rows tall, topped back up as
+    /// they're cleared. Short boards get fewer, to leave room to spawn pieces
+    #[must_use]
+    pub fn cheese_rows(self, board_height: usize) -> usize {
+        if self.has_cheese() {
+            CHEESE_RACE_VISIBLE_ROWS.min(board_height / 2)
+        } else {
+            0
+        }
+    }
+
+    /// Whether garbage rises from the bottom of the board on a timer
+    #[must_use]
+    pub const fn has_rising_garbage(self) -> bool {
+        matches!(self, Self::Survival)
+    }
+
+    /// Returns true once the goal has been reached or the time has run out
+    #[must_use]
+    pub fn is_complete(self, engine: &Engine) -> bool {
+        self.line_goal()
+            .is_some_and(|goal| engine.lines_cleared() >= goal)
+            || self
+                .time_limit()
+                .is_some_and(|limit| engine.stats().time() >= limit)
+            || (self.has_cheese() && engine.garbage_cleared() >= engine.cheese_lines())
+    }
+
+    /// The progress shown on the HUD, one line each
+    #[must_use]
+    pub fn hud_lines(self, engine: &Engine) -> [String; 3] {
+        let time = engine.stats().time();
+        let lines = engine.lines_cleared();
+        match self {
+            Self::Marathon => [
+                format!("Score: {}", engine.score()),
+                format!("Level: {}", engine.level()),
+                format!("Lines: {lines}/{MARATHON_LINES}"),
+            ],
+            Self::Sprint => [
+                format!("Time: {}", format_time(time)),
+                format!("Lines: {lines}/{SPRINT_LINES}"),
+                format!("Pieces: {}", engine.stats().pieces_placed),
+            ],
+            Self::Ultra => [
+                format!("Score: {}", engine.score()),
+                format!(
+                    "Left: {}",
+                    format_time(ULTRA_TIME_LIMIT.saturating_sub(time))
+                ),
+                format!("Lines: {lines}"),
+            ],
+            Self::Zen => [
+                format!("Score: {}", engine.score()),
+                format!("Lines: {lines}"),
+                format!("Time: {}", format_time(time)),
+            ],
+            Self::CheeseRace => [
+                format!("Time: {}", format_time(time)),
+                format!(
+                    "Garbage: {}/{}",
+                    engine.garbage_cleared(),
+                    engine.cheese_lines()
+                ),
+                format!("Pieces: {}", engine.stats().pieces_placed),
+            ],
+            Self::Survival => [
+                format!("Time: {}", format_time(time)),
+                format!("Lines: {lines}"),
+                format!(
+                    "Rising: {}",
+                    format_time(engine.time_until_garbage_rises().unwrap_or_default())
+                ),
+            ],
+            Self::Battle => [
+                format!("Score: {}", engine.score()),
+                format!("Lines: {lines}"),
+                format!("Sent: {}", engine.stats().lines_sent),
+            ],
+        }
+    }
+}
+
+/// The time between the garbage row `rows_risen` rows into a game of Survival and the next one
+#[must_use]
+pub fn garbage_rise_interval(rows_risen: u32) -> Duration {
+    let exponent = i32::try_from(rows_risen).unwrap_or(i32::MAX);
+    SURVIVAL_FIRST_RISE
+        .mul_f64(SURVIVAL_ACCELERATION.powi(exponent))
+        .max(SURVIVAL_FASTEST_RISE)
+}
+
+impl FromStr for GameMode {
+    type Err = UnknownGameMode;
+
+    fn from_str(name: &str) -> Result<Self, Self::Err> {
+        Self::ALL
+            .into_iter()
+            .find(|mode| mode.name() == name)
+            .ok_or(UnknownGameMode)
+    }
+}
+
+/// Returned when parsing a name that doesn't belong to any [`GameMode`]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct UnknownGameMode;
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn survival_garbage_rises_faster_and_faster_down_to_the_fastest_rise() {
+        assert_eq!(garbage_rise_interval(0), SURVIVAL_FIRST_RISE);
+        assert_eq!(
+            garbage_rise_interval(1),
+            SURVIVAL_FIRST_RISE.mul_f64(SURVIVAL_ACCELERATION)
+        );
+
+        let intervals: Vec<_> = (0..100).map(garbage_rise_interval).collect();
+        assert!(intervals.windows(2).all(|pair| pair[1] <= pair[0]));
+        assert_eq!(intervals.last(), Some(&SURVIVAL_FASTEST_RISE));
+        assert_eq!(garbage_rise_interval(u32::MAX), SURVIVAL_FASTEST_RISE);
+    }
+}
@@ -0,0 +1,97 @@
+//! Rows of garbage pushed into the board from below, each with a single hole to clear it through
+use std::str::FromStr;
+
+use gemini_engine::core::{ColChar, Colour};
+use rand::{rngs::StdRng, Rng, SeedableRng};
+
+/// The colour garbage is drawn in, so it stands out from the placed pieces
+pub const GARBAGE_COLOUR: ColChar = ColChar::SOLID.with_colour(Colour::rgb(128, 128, 128));
+
+/// Mixed into the game's seed so garbage holes don't follow the same rolls as the pieces
+const GARBAGE_SEED_SALT: u64 = 0x0067_6172_6261_6765;
+
+/// Where the holes in consecutive garbage rows go
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum HolePlacement {
+    /// Every row has its hole in the same column
+    Clean,
+    /// Each row has a `percent` chance of moving its hole to a different column from the row
+    /// before. At 100 every hole is in a new column
+    Messy { percent: u8 },
+}
+
+impl Default for HolePlacement {
+    fn default() -> Self {
+        Self::Messy { percent: 100 }
+    }
+}
+
+/// Parses `clean`, or a messiness percentage from 0 to 100
+impl FromStr for HolePlacement {
+    type Err = InvalidHolePlacement;
+
+    fn from_str(value: &str) -> Result<Self, Self::Err> {
+        match value {
+            "clean" => Ok(Self::Clean),
+            _ => match value.parse() {
+                Ok(percent) if percent <= 100 => Ok(Self::Messy { percent }),
+                _ => Err(InvalidHolePlacement),
+            },
+        }
+    }
+}
+
+/// Returned when parsing something that is neither `clean` nor a percentage
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct InvalidHolePlacement;
+
+/// Picks the hole of every garbage row from its own RNG seeded from the game's seed, so the same
+/// seed always produces the same garbage
+#[derive(Debug, Clone)]
+pub struct GarbageGenerator {
+    rng: StdRng,
+    placement: HolePlacement,
+    board_width: usize,
+    /// The column of the last row's hole
+    hole: Option<usize>,
+}
+
+impl GarbageGenerator {
+    #[must_use]
+    pub fn new(placement: HolePlacement, board_width: usize, seed: u64) -> Self {
+        Self {
+            rng: StdRng::seed_from_u64(seed ^ GARBAGE_SEED_SALT),
+            placement,
+            board_width,
+            hole: None,
+        }
+    }
+
+    /// The column of the next row's hole, between 1 and the board width
+    pub fn next_hole(&mut self) -> usize {
+        let moves = match (self.hole, self.placement) {
+            (None, _) => true,
+            (Some(_), HolePlacement::Clean) => false,
+            (Some(_), HolePlacement::Messy { percent }) => {
+                self.rng.gen_ratio(u32::from(percent), 100)
+            }
+        };
+
+        let hole = match self.hole {
+            Some(hole) if !moves => hole,
+            // A single column board has nowhere else to put the hole
+            Some(hole) if self.board_width > 1 => {
+                let new_hole = self.rng.gen_range(1..self.board_width);
+                if new_hole >= hole {
+                    new_hole + 1
+                } else {
+                    new_hole
+                }
+            }
+            _ => self.rng.gen_range(1..=self.board_width),
+        };
+
+        self.hole = Some(hole);
+        hole
+    }
+}
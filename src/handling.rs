@@ -0,0 +1,186 @@
+use std::time::Duration;
+
+use serde::{Deserialize, Serialize};
+
+use crate::engine::FRAME_DURATION;
+
+/// How held inputs repeat
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub struct Handling {
+    /// Delayed Auto Shift: how long a shift has to be held before it starts repeating
+    pub das: Duration,
+    /// Auto Repeat Rate: the time between each repeated shift once DAS has charged. Zero moves
+    /// the block straight to the wall
+    pub arr: Duration,
+    /// How many times faster than gravity a soft dropped block falls
+    pub soft_drop_factor: f64,
+}
+
+impl Default for Handling {
+    fn default() -> Self {
+        Self {
+            das: Duration::from_millis(167),
+            arr: Duration::from_millis(33),
+            soft_drop_factor: 20.0,
+        }
+    }
+}
+
+/// Tracks the held shift direction and how far it has charged. This is kept separate from the
+/// active block so that DAS stays charged when a new block spawns
+#[derive(Debug, Clone)]
+pub struct AutoShift {
+    handling: Handling,
+    left_held: bool,
+    right_held: bool,
+    /// The direction of the most recently pressed shift that's still held
+    direction: Option<i64>,
+    held_time: Duration,
+    repeats_done: u128,
+}
+
+impl AutoShift {
+    #[must_use]
+    pub const fn new(handling: Handling) -> Self {
+        Self {
+            handling,
+            left_held: false,
+            right_held: false,
+            direction: None,
+            held_time: Duration::ZERO,
+            repeats_done: 0,
+        }
+    }
+
+    /// Call when a shift starts being held. `direction` is -1 for left and 1 for right
+    pub const fn press(&mut self, direction: i64) {
+        if direction < 0 {
+            self.left_held = true;
+        } else {
+            self.right_held = true;
+        }
+        self.charge(direction);
+    }
+
+    /// Call when a shift stops being held. If the opposite shift is still held, it takes over and
+    /// starts charging from zero
+    pub const fn release(&mut self, direction: i64) {
+        let opposite_is_held = if direction < 0 {
+            self.left_held = false;
+            self.right_held
+        } else {
+            self.right_held = false;
+            self.left_held
+        };
+
+        if matches!(self.direction, Some(current) if current == direction) {
+            if opposite_is_held {
+                self.charge(-direction);
+            } else {
+                self.direction = None;
+            }
+        }
+    }
+
+    const fn charge(&mut self, direction: i64) {
+        self.direction = Some(direction);
+        self.held_time = Duration::ZERO;
+        self.repeats_done = 0;
+    }
+
+    /// Advance by one frame. Returns the direction to shift in and the number of times to shift,
+    /// where `u128::MAX` means all the way to the wall
+    pub fn tick(&mut self) -> Option<(i64, u128)> {
+        let direction = self.direction?;
+        self.held_time += FRAME_DURATION;
+
+        let charged_time = self.held_time.checked_sub(self.handling.das)?;
+        if self.handling.arr.is_zero() {
+            return Some((direction, u128::MAX));
+        }
+
+        // The first repeat happens as soon as DAS is charged, then one every ARR
+        let repeats_due = charged_time.as_nanos() / self.handling.arr.as_nanos() + 1;
+        let repeats = repeats_due - self.repeats_done;
+        self.repeats_done = repeats_due;
+
+        Some((direction, repeats))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const DAS_FRAMES: u32 = 10;
+
+    fn with_arr(arr: Duration) -> AutoShift {
+        AutoShift::new(Handling {
+            das: FRAME_DURATION * DAS_FRAMES,
+            arr,
+            ..Handling::default()
+        })
+    }
+
+    /// Tick until DAS is about to charge, checking nothing repeats before then
+    fn charge(auto_shift: &mut AutoShift) {
+        for _ in 1..DAS_FRAMES {
+            assert_eq!(auto_shift.tick(), None);
+        }
+    }
+
+    #[test]
+    fn the_first_repeat_is_on_the_frame_das_charges() {
+        let mut auto_shift = with_arr(FRAME_DURATION * 2);
+        auto_shift.press(1);
+        charge(&mut auto_shift);
+        assert_eq!(auto_shift.tick(), Some((1, 1)));
+    }
+
+    #[test]
+    fn repeats_follow_the_arr() {
+        let mut auto_shift = with_arr(FRAME_DURATION * 2);
+        auto_shift.press(-1);
+        charge(&mut auto_shift);
+        let repeats: Vec<_> = (0..6)
+            .map(|_| auto_shift.tick().map(|(_, repeats)| repeats))
+            .collect();
+        assert_eq!(repeats, [1, 0, 1, 0, 1, 0].map(Some));
+
+        // An ARR shorter than a frame repeats more than once a frame
+        let mut auto_shift = with_arr(FRAME_DURATION / 2);
+        auto_shift.press(-1);
+        charge(&mut auto_shift);
+        assert_eq!(auto_shift.tick(), Some((-1, 1)));
+        assert_eq!(auto_shift.tick(), Some((-1, 2)));
+    }
+
+    #[test]
+    fn no_arr_shifts_to_the_wall() {
+        let mut auto_shift = with_arr(Duration::ZERO);
+        auto_shift.press(1);
+        charge(&mut auto_shift);
+        assert_eq!(auto_shift.tick(), Some((1, u128::MAX)));
+    }
+
+    #[test]
+    fn releasing_hands_over_to_the_opposite_shift_with_a_fresh_charge() {
+        let mut auto_shift = with_arr(FRAME_DURATION * 2);
+        auto_shift.press(-1);
+        charge(&mut auto_shift);
+        auto_shift.press(1);
+        auto_shift.release(1);
+
+        charge(&mut auto_shift);
+        assert_eq!(auto_shift.tick(), Some((-1, 1)));
+
+        // Letting go of a shift that was overridden leaves the newer one charging
+        auto_shift.press(1);
+        auto_shift.release(-1);
+        charge(&mut auto_shift);
+        assert_eq!(auto_shift.tick(), Some((1, 1)));
+
+        auto_shift.release(1);
+        assert_eq!(auto_shift.tick(), None);
+    }
+}
@@ -0,0 +1,66 @@
+use crate::FPS;
+
+/// The number of lines that need to be cleared to advance a level
+pub const LINES_PER_LEVEL: u32 = 10;
+
+/// Gravity at which pieces drop straight to the floor, measured in rows per frame
+pub const MAX_GRAVITY: f64 = 20.0;
+
+/// The guideline's time in seconds for a piece to fall one row, starting from level 1. Calculated
+/// with `(0.8 - (level - 1) * 0.007) ^ (level - 1)`. Every level after the table ends is 20G
+const SECONDS_PER_ROW: [f64; 18] = [
+    1.0, 0.793, 0.6178, 0.47273, 0.3552, 0.262, 0.18968, 0.13473, 0.09388, 0.06415, 0.04298,
+    0.02822, 0.01815, 0.01144, 0.00706, 0.00426, 0.00252, 0.00146,
+];
+
+/// The level reached after clearing `lines_cleared` lines, starting from `starting_level`
+#[must_use]
+pub const fn level_for_lines(starting_level: u32, lines_cleared: u32) -> u32 {
+    let level = lines_cleared / LINES_PER_LEVEL + 1;
+    if level > starting_level {
+        level
+    } else {
+        starting_level
+    }
+}
+
+/// The gravity at `level`, in rows per frame. A gravity of 0.5 means the piece falls one row every
+/// two frames, while a gravity of 3 means the piece falls three rows every frame
+#[must_use]
+pub fn gravity(level: u32) -> f64 {
+    let index = level.saturating_sub(1) as usize;
+    SECONDS_PER_ROW.get(index).map_or(MAX_GRAVITY, |seconds| {
+        (1.0 / (seconds * f64::from(FPS))).min(MAX_GRAVITY)
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn every_ten_lines_is_a_level_up() {
+        let levels: Vec<u32> = [0, 9, 10, 19, 20, 95]
+            .map(|lines| level_for_lines(1, lines))
+            .into();
+        assert_eq!(levels, [1, 1, 2, 2, 3, 10]);
+
+        // Clearing lines doesn't level up until it catches up with the starting level
+        assert_eq!(level_for_lines(5, 39), 5);
+        assert_eq!(level_for_lines(5, 40), 5);
+        assert_eq!(level_for_lines(5, 50), 6);
+    }
+
+    #[test]
+    fn gravity_gets_faster_until_pieces_drop_straight_down() {
+        assert!((gravity(1) - 1.0 / f64::from(FPS)).abs() < 1e-9);
+        for level in 1..=SECONDS_PER_ROW.len() as u32 {
+            assert!(gravity(level) < gravity(level + 1), "level {level}");
+        }
+
+        // The highest starting level, and anything past it, is 20G
+        for level in [19, 20, 100, u32::MAX] {
+            assert!((gravity(level) - MAX_GRAVITY).abs() < f64::EPSILON);
+        }
+    }
+}
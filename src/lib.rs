@@ -0,0 +1,30 @@
+//! The rules of Tetris, free of any terminal, audio or input handling. Drive an [`Engine`] by
+//! feeding it [`Input`]s and calling [`Engine::tick`] once per frame, then read its state back to
+//! render it however you like
+pub mod attack;
+pub mod block_manager;
+pub mod board;
+pub mod bot;
+pub mod collision_manager;
+mod engine;
+pub mod game_mode;
+pub mod garbage;
+pub mod handling;
+pub mod level;
+pub mod lock_delay;
+pub mod piece_set;
+pub mod randomizer;
+pub mod scaling;
+pub mod scoring;
+mod settings;
+pub mod stack_effect;
+mod stats;
+
+pub use block_manager::{tetris_core, Block, BlockType, LastMove, RotationSystem, Tetromino, Turn};
+pub use board::Board;
+pub use engine::{Engine, Input, FPS};
+pub use game_mode::GameMode;
+pub use piece_set::PieceSet;
+pub use randomizer::RandomizerKind;
+pub use settings::Settings;
+pub use stats::{format_time, Stats};
@@ -0,0 +1,220 @@
+use std::time::Duration;
+
+use crate::engine::FRAME_DURATION;
+
+/// The rule deciding when moving or rotating a resting block restarts its lock delay
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum LockResetMode {
+    /// Every successful move or rotation restarts the lock delay, so a block can be stalled
+    /// forever
+    Infinite,
+    /// Moves and rotations restart the lock delay, but only `max_resets` times per block
+    MoveReset { max_resets: u32 },
+    /// The lock delay only restarts when the block falls to a new lowest row
+    StepReset,
+    /// The lock delay never restarts. The block locks after resting for the delay in total
+    Classic,
+}
+
+/// How long a block can rest on the ground before it locks in place
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct LockDelay {
+    pub delay: Duration,
+    pub reset_mode: LockResetMode,
+    /// Give a block all of its [`LockResetMode::MoveReset`] resets back whenever it falls to a
+    /// new lowest row
+    pub lowest_row_refills_resets: bool,
+}
+
+impl Default for LockDelay {
+    fn default() -> Self {
+        Self {
+            delay: Duration::from_millis(500),
+            reset_mode: LockResetMode::MoveReset { max_resets: 15 },
+            lowest_row_refills_resets: true,
+        }
+    }
+}
+
+/// Tracks how long the active block has rested on the ground and how many times that time has
+/// been reset
+#[derive(Debug, Clone)]
+pub struct LockTimer {
+    settings: LockDelay,
+    resting_time: Duration,
+    resets_used: u32,
+    lowest_row: i64,
+}
+
+impl LockTimer {
+    #[must_use]
+    pub const fn new(settings: LockDelay) -> Self {
+        Self {
+            settings,
+            resting_time: Duration::ZERO,
+            resets_used: 0,
+            lowest_row: i64::MIN,
+        }
+    }
+
+    /// Call when a new block spawns at `row`
+    pub const fn restart(&mut self, row: i64) {
+        self.resting_time = Duration::ZERO;
+        self.resets_used = 0;
+        self.lowest_row = row;
+    }
+
+    /// Call when the active block successfully moves or rotates, leaving it at `row`
+    pub fn on_block_moved(&mut self, row: i64) {
+        if row > self.lowest_row {
+            self.lowest_row = row;
+            if self.settings.lowest_row_refills_resets {
+                self.resets_used = 0;
+            }
+            if self.settings.reset_mode != LockResetMode::Classic {
+                self.resting_time = Duration::ZERO;
+                return;
+            }
+        }
+
+        match self.settings.reset_mode {
+            LockResetMode::Infinite => self.resting_time = Duration::ZERO,
+            // Only moves made while resting count towards the limit
+            LockResetMode::MoveReset { max_resets } => {
+                if self.resting_time > Duration::ZERO && self.resets_used < max_resets {
+                    self.resets_used += 1;
+                    self.resting_time = Duration::ZERO;
+                }
+            }
+            LockResetMode::StepReset | LockResetMode::Classic => (),
+        }
+    }
+
+    /// Call when the active block is pushed up a row by rising garbage, so the row it was pushed
+    /// out of counts as a new lowest row once again
+    pub const fn on_pushed_up(&mut self) {
+        self.lowest_row -= 1;
+    }
+
+    /// Advance the timer by one frame of the block resting on the ground. Returns true if the
+    /// block should lock
+    pub fn tick_resting(&mut self) -> bool {
+        self.resting_time += FRAME_DURATION;
+        self.resting_time >= self.settings.delay
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const DELAY_FRAMES: u32 = 3;
+
+    fn timer(reset_mode: LockResetMode, lowest_row_refills_resets: bool) -> LockTimer {
+        let mut timer = LockTimer::new(LockDelay {
+            delay: FRAME_DURATION * DELAY_FRAMES,
+            reset_mode,
+            lowest_row_refills_resets,
+        });
+        timer.restart(0);
+        timer
+    }
+
+    /// Rest for `frames` frames without locking
+    fn rest(timer: &mut LockTimer, frames: u32) {
+        for _ in 0..frames {
+            assert!(!timer.tick_resting(), "the block locked too early");
+        }
+    }
+
+    /// The number of frames the block can rest for from now, including the frame it locks on
+    fn frames_left(timer: &LockTimer) -> u32 {
+        let mut timer = timer.clone();
+        (1..=100)
+            .find(|_| timer.tick_resting())
+            .expect("the block should lock eventually")
+    }
+
+    #[test]
+    fn infinite_resets_on_every_move() {
+        let mut timer = timer(LockResetMode::Infinite, true);
+        for _ in 0..100 {
+            rest(&mut timer, DELAY_FRAMES - 1);
+            timer.on_block_moved(0);
+        }
+        assert_eq!(frames_left(&timer), DELAY_FRAMES);
+    }
+
+    #[test]
+    fn move_reset_locks_once_the_resets_run_out() {
+        let mut timer = timer(LockResetMode::MoveReset { max_resets: 2 }, true);
+        // Moving before resting doesn't use up a reset
+        timer.on_block_moved(0);
+
+        for _ in 0..2 {
+            rest(&mut timer, 1);
+            timer.on_block_moved(0);
+            assert_eq!(frames_left(&timer), DELAY_FRAMES);
+        }
+        rest(&mut timer, 1);
+        timer.on_block_moved(0);
+        assert_eq!(frames_left(&timer), DELAY_FRAMES - 1);
+
+        // Moving every frame can't stall it any more
+        rest(&mut timer, DELAY_FRAMES - 2);
+        timer.on_block_moved(0);
+        assert!(timer.tick_resting());
+    }
+
+    #[test]
+    fn step_reset_only_resets_on_a_new_lowest_row() {
+        let mut timer = timer(LockResetMode::StepReset, true);
+        rest(&mut timer, 2);
+        timer.on_block_moved(0);
+        assert_eq!(frames_left(&timer), DELAY_FRAMES - 2);
+
+        timer.on_block_moved(1);
+        assert_eq!(frames_left(&timer), DELAY_FRAMES);
+    }
+
+    #[test]
+    fn classic_never_resets() {
+        let mut timer = timer(LockResetMode::Classic, true);
+        rest(&mut timer, 2);
+        timer.on_block_moved(0);
+        timer.on_block_moved(1);
+        assert_eq!(frames_left(&timer), DELAY_FRAMES - 2);
+    }
+
+    #[test]
+    fn a_new_lowest_row_refills_move_resets() {
+        for refills in [true, false] {
+            let mut timer = timer(LockResetMode::MoveReset { max_resets: 1 }, refills);
+            rest(&mut timer, 1);
+            timer.on_block_moved(0);
+
+            // Falling always restarts the delay, but only gives the resets back with the rule
+            timer.on_block_moved(1);
+            assert_eq!(frames_left(&timer), DELAY_FRAMES);
+            rest(&mut timer, 1);
+            timer.on_block_moved(1);
+            let expected = if refills {
+                DELAY_FRAMES
+            } else {
+                DELAY_FRAMES - 1
+            };
+            assert_eq!(frames_left(&timer), expected);
+        }
+    }
+
+    #[test]
+    fn pushing_a_block_up_makes_its_old_row_new_again() {
+        let mut timer = timer(LockResetMode::StepReset, true);
+        timer.on_block_moved(1);
+        rest(&mut timer, 2);
+
+        timer.on_pushed_up();
+        timer.on_block_moved(1);
+        assert_eq!(frames_left(&timer), DELAY_FRAMES);
+    }
+}
@@ -1,26 +1,86 @@
 use console_input::keypress::enable_raw_mode;
 use gemini_engine::gameloop::MainLoopRoot;
 mod game;
-use game::Game;
+use game::{Feed, Game, Spectators, Watch};
+mod args;
+// Written before clippy could run with the lint groups in Cargo.toml, and left as it was
+#[allow(clippy::unwrap_used, clippy::derive_partial_eq_without_eq)]
 mod audio;
+mod net;
+use args::{Args, Network};
+use net::Connection;
 
-const BLOCK_PLACE_COOLDOWN: u32 = 30;
-const PIECE_PREVIEW_COUNT: usize = 3;
-const CONTROLS_HELP_TEXT: &str = "Controls:
-C to hold
-Left/Right to shift
-Space hard | Down soft
-Z AC | Up/X C rotation
-Esc to pause";
+const USAGE: &str = "Usage: tetris [--seed SEED] [--width WIDTH] [--height HEIGHT] [--level LEVEL]
+              [--lock-delay MS] [--lock-reset infinite|move|step|classic] [--no-lowest-row-rule]
+              [--das MS] [--arr MS] [--sdf FACTOR] [--rotation srs|srs+|ars|nrs]
+              [--pieces FILE] [--randomizer 7-bag|14-bag|random|tgm|nes|classic]
+              [--mode marathon|sprint|ultra|zen|cheese|survival|battle] [--cheese-lines LINES]
+              [--holes clean|PERCENT] [--stack visible|invisible|fading]
+              [--big] [--bots BOTS] [--bot-delay MS] [--players 1|2] [--shared-seed]
+              [--host PORT | --connect ADDR] [--input-delay FRAMES] [--spectate ADDR]
+       tetris --watch ADDR";
 
 fn main() {
+    let args = match Args::parse(std::env::args().skip(1)) {
+        Ok(args) => args,
+        Err(err) => {
+            eprintln!("{err}\n{USAGE}");
+            std::process::exit(1);
+        }
+    };
+
+    if let Some(endpoint) = &args.watch {
+        let (feed, layout) = match Feed::connect(endpoint) {
+            Ok(watched) => watched,
+            Err(err) => {
+                eprintln!("{err}");
+                std::process::exit(1);
+            }
+        };
+
+        enable_raw_mode();
+        Watch::new(feed, layout).main_loop();
+        return;
+    }
+
+    // Listen for spectators before waiting on the other player, so a bad address is caught early
+    let spectators = match args.spectate.as_ref().map(Spectators::bind).transpose() {
+        Ok(spectators) => spectators,
+        Err(err) => {
+            eprintln!("failed to listen for spectators: {err}");
+            std::process::exit(1);
+        }
+    };
+
+    let (seed, connection) = match &args.network {
+        None => (args.seed, None),
+        Some(network) => match connect(network, &args) {
+            Ok((seed, connection)) => (seed, Some(connection)),
+            Err(err) => {
+                eprintln!("{err}");
+                std::process::exit(1);
+            }
+        },
+    };
+
     enable_raw_mode();
 
-    let mut game = Game::new(
-        BLOCK_PLACE_COOLDOWN,
-        PIECE_PREVIEW_COUNT,
-        CONTROLS_HELP_TEXT,
-    );
+    let mut game = Game::new(&args.settings, seed, connection, spectators);
 
     game.main_loop();
 }
+
+/// Find the other player for an online game. Returns the seed to play, which is always the host's
+fn connect(network: &Network, args: &Args) -> Result<(u64, Connection), net::NetError> {
+    match network {
+        Network::Host(port) => {
+            println!("Waiting for the other player to connect on port {port}...");
+            let connection = Connection::host(*port, &args.settings, args.seed)?;
+            Ok((args.seed, connection))
+        }
+        Network::Connect(addr) => {
+            let (connection, seed) = Connection::connect(addr.as_str(), &args.settings)?;
+            Ok((seed, connection))
+        }
+    }
+}
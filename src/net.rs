@@ -0,0 +1,475 @@
+//! Playing against someone on another machine over TCP
+//!
+//! Each player runs their own board, and a copy of their opponent's board that is driven by the
+//! opponent's inputs. Boards are deterministic, so as long as both sides feed the copy exactly
+//! what happened on the real board, frame by frame, the two stay identical without ever sending
+//! the board itself. Garbage is never sent either: it is read off the copy of the opponent's board
+//! whenever it attacks.
+//!
+//! The opponent's frames take time to arrive, so rather than wait for them the copy of their board
+//! that's shown is predicted ahead, and rolled back and played again when their frames arrive.
+//! Inputs can also be delayed by a few frames before they're played, which sends them ahead of
+//! time so there's less to predict.
+//!
+//! # Protocol
+//!
+//! Messages are JSON objects, one per line, tagged by their `type`:
+//!
+//! 1. The connecting side sends `{"type":"hello","version":2,"options":...,"handling":...}`,
+//!    where `options` is a fingerprint of every option that affects the game, and `handling` is
+//!    their DAS, ARR and soft drop factor, which each player picks for themselves.
+//! 2. The host replies with `{"type":"welcome","seed":...,"handling":...}` to start the game with
+//!    the host's seed and handling, or with `{"type":"reject","reason":"..."}` if the version or
+//!    options don't match, and hangs up.
+//! 3. From then on both sides send a `{"type":"frame","frame":n,"inputs":[...],"garbage":g}` for
+//!    every frame they play, numbered from 0. `inputs` lists the inputs pressed (`{"press":
+//!    "hard_drop"}`) and released (`{"release":"shift_left"}`) that frame, in order, and `garbage`
+//!    is the number of garbage lines that were queued on the board that frame. A board applies a
+//!    frame's inputs, then queues its garbage, then ticks. With an input delay of `d` frames, a
+//!    side starts by sending frames 0 to `d - 1` with no inputs, and every frame sent after that is
+//!    played `d` frames after it was sent.
+//!
+//! Either side closing the connection ends the game.
+use std::{
+    collections::BTreeMap,
+    io::{self, BufRead, BufReader, BufWriter, Read, Write},
+    net::{TcpListener, TcpStream, ToSocketAddrs},
+    sync::mpsc::{self, Receiver, TryRecvError},
+    thread,
+};
+
+use serde::{de::DeserializeOwned, Deserialize, Serialize};
+use tetris::{handling::Handling, Engine, Input, Settings};
+use thiserror::Error;
+
+/// Bumped whenever the messages or the rules of the game change, so that different versions
+/// don't try to play each other
+pub const PROTOCOL_VERSION: u32 = 2;
+
+/// The longest line read as a message. This is far longer than a frame of the biggest boards, and
+/// keeps the other side from using up memory with a line that never ends
+const MAX_MESSAGE_LEN: u64 = 8 << 20;
+
+const FNV_OFFSET_BASIS: u64 = 0xcbf2_9ce4_8422_2325;
+const FNV_PRIME: u64 = 0x0000_0100_0000_01b3;
+
+#[derive(Debug, Error)]
+pub enum NetError {
+    #[error("connection failed: {0}")]
+    Io(#[from] io::Error),
+    #[error("received an invalid message: {0}")]
+    InvalidMessage(#[from] serde_json::Error),
+    #[error("received a message longer than {MAX_MESSAGE_LEN} bytes")]
+    MessageTooLong,
+    #[error("the connection closed before the game started")]
+    HungUp,
+    #[error("unexpected message before the game started")]
+    UnexpectedMessage,
+    #[error("the game was rejected: {0}")]
+    Rejected(String),
+}
+
+/// An input pressed or released on a board
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum InputEvent {
+    Press(Input),
+    Release(Input),
+}
+
+impl InputEvent {
+    /// Press or release the input on `engine`
+    pub fn play_on(self, engine: &mut Engine) {
+        match self {
+            Self::Press(input) => engine.apply_input(input),
+            Self::Release(input) => engine.release_input(input),
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum Message {
+    /// Sent by the connecting side to ask to play
+    Hello {
+        version: u32,
+        options: u64,
+        /// Missing from older versions, which are turned down anyway
+        #[serde(default)]
+        handling: Handling,
+    },
+    /// Sent by the host to start the game
+    Welcome { seed: u64, handling: Handling },
+    /// Sent by the host to turn the game down
+    Reject { reason: String },
+    /// Everything that happened on the sender's board in one frame
+    Frame {
+        frame: u64,
+        inputs: Vec<InputEvent>,
+        garbage: u32,
+    },
+}
+
+/// A connection to the other player
+pub struct Connection {
+    writer: BufWriter<TcpStream>,
+    incoming: Incoming<Message>,
+    /// Cleared when a message can't be sent
+    is_open: bool,
+    opponent_handling: Handling,
+}
+
+impl Connection {
+    /// Wait for the other player to connect on `port`, then start a game with `seed` if they're
+    /// playing with the same options
+    pub fn host(port: u16, settings: &Settings, seed: u64) -> Result<Self, NetError> {
+        Self::accept(&TcpListener::bind(("0.0.0.0", port))?, settings, seed)
+    }
+
+    /// Wait for the other player to connect to `listener`, then start a game with `seed` if
+    /// they're playing with the same options
+    pub fn accept(
+        listener: &TcpListener,
+        settings: &Settings,
+        seed: u64,
+    ) -> Result<Self, NetError> {
+        let (stream, _) = listener.accept()?;
+        let (mut writer, mut reader) = split(stream)?;
+
+        let Message::Hello {
+            version,
+            options,
+            handling,
+        } = read_message(&mut reader)?
+        else {
+            return Err(NetError::UnexpectedMessage);
+        };
+        let rejection = if version != PROTOCOL_VERSION {
+            Some(format!(
+                "the host speaks version {PROTOCOL_VERSION} of the protocol, not {version}"
+            ))
+        } else if options != options_fingerprint(settings) {
+            Some("the players are using different options".to_string())
+        } else {
+            None
+        };
+        if let Some(reason) = rejection {
+            write_message(
+                &mut writer,
+                &Message::Reject {
+                    reason: reason.clone(),
+                },
+            )?;
+            return Err(NetError::Rejected(reason));
+        }
+
+        write_message(
+            &mut writer,
+            &Message::Welcome {
+                seed,
+                handling: settings.handling,
+            },
+        )?;
+        Ok(Self::start(writer, reader, handling))
+    }
+
+    /// Connect to a player hosting at `addr`. Returns the connection along with the seed to play
+    pub fn connect(addr: impl ToSocketAddrs, settings: &Settings) -> Result<(Self, u64), NetError> {
+        let (mut writer, mut reader) = split(TcpStream::connect(addr)?)?;
+
+        write_message(
+            &mut writer,
+            &Message::Hello {
+                version: PROTOCOL_VERSION,
+                options: options_fingerprint(settings),
+                handling: settings.handling,
+            },
+        )?;
+        let (seed, handling) = match read_message(&mut reader)? {
+            Message::Welcome { seed, handling } => (seed, handling),
+            Message::Reject { reason } => return Err(NetError::Rejected(reason)),
+            _ => return Err(NetError::UnexpectedMessage),
+        };
+
+        Ok((Self::start(writer, reader, handling), seed))
+    }
+
+    fn start(
+        writer: BufWriter<TcpStream>,
+        reader: BufReader<TcpStream>,
+        opponent_handling: Handling,
+    ) -> Self {
+        Self {
+            writer,
+            incoming: Incoming::start(reader),
+            is_open: true,
+            opponent_handling,
+        }
+    }
+
+    /// How the other player's held inputs repeat, which their board has to be played with
+    pub const fn opponent_handling(&self) -> Handling {
+        self.opponent_handling
+    }
+
+    /// Send a message. The connection is closed if it can't be sent
+    pub fn send(&mut self, message: &Message) -> io::Result<()> {
+        let result = write_message(&mut self.writer, message);
+        if result.is_err() {
+            self.is_open = false;
+        }
+        result
+    }
+
+    /// Every message received since the last call
+    pub fn receive(&mut self) -> Vec<Message> {
+        self.incoming.receive()
+    }
+
+    /// Whether the other player is still connected
+    pub const fn is_open(&self) -> bool {
+        self.is_open && self.incoming.is_open()
+    }
+}
+
+/// Messages read from a stream on a thread of their own, so receiving never blocks the game
+pub struct Incoming<M> {
+    receiver: Receiver<M>,
+    is_open: bool,
+}
+
+impl<M: DeserializeOwned + Send + 'static> Incoming<M> {
+    /// Start reading messages on a new thread, until the stream closes
+    pub fn start(mut reader: impl BufRead + Send + 'static) -> Self {
+        let (sender, receiver) = mpsc::channel();
+        thread::spawn(move || {
+            while let Ok(message) = read_message(&mut reader) {
+                if sender.send(message).is_err() {
+                    break;
+                }
+            }
+        });
+
+        Self {
+            receiver,
+            is_open: true,
+        }
+    }
+
+    /// Every message received since the last call
+    pub fn receive(&mut self) -> Vec<M> {
+        let mut messages = vec![];
+        loop {
+            match self.receiver.try_recv() {
+                Ok(message) => messages.push(message),
+                Err(TryRecvError::Empty) => break,
+                Err(TryRecvError::Disconnected) => {
+                    self.is_open = false;
+                    break;
+                }
+            }
+        }
+
+        messages
+    }
+
+    /// Whether the stream is still open
+    pub const fn is_open(&self) -> bool {
+        self.is_open
+    }
+}
+
+/// Split a stream into a buffered writer and reader
+fn split(stream: TcpStream) -> io::Result<(BufWriter<TcpStream>, BufReader<TcpStream>)> {
+    // Frames are tiny and need to arrive as soon as possible
+    stream.set_nodelay(true)?;
+    Ok((BufWriter::new(stream.try_clone()?), BufReader::new(stream)))
+}
+
+pub fn read_message<M: DeserializeOwned>(reader: &mut impl BufRead) -> Result<M, NetError> {
+    let mut line = String::new();
+    let len = reader.by_ref().take(MAX_MESSAGE_LEN).read_line(&mut line)?;
+    if len == 0 {
+        return Err(NetError::HungUp);
+    }
+    if len as u64 == MAX_MESSAGE_LEN && !line.ends_with('\n') {
+        return Err(NetError::MessageTooLong);
+    }
+    Ok(serde_json::from_str(&line)?)
+}
+
+pub fn write_message(writer: &mut impl Write, message: &impl Serialize) -> io::Result<()> {
+    serde_json::to_writer(&mut *writer, message)?;
+    writer.write_all(b"\n")?;
+    writer.flush()
+}
+
+/// A hash of every option that changes how the game plays. Both players' boards have to be set up
+/// the same way for the copies of each other's boards to stay in sync
+fn options_fingerprint(settings: &Settings) -> u64 {
+    // Kicks are kept in a HashMap, which is printed in a different order every run, so they're
+    // sorted first
+    let pieces: Vec<_> = settings
+        .piece_set
+        .pieces()
+        .iter()
+        .map(|piece| {
+            (
+                &piece.name,
+                &piece.rotation_states,
+                piece.kicks.iter().collect::<BTreeMap<_, _>>(),
+                piece.t_spin_upgrade_kick,
+                piece.spawn_rotation,
+                piece.spawn_offset,
+            )
+        })
+        .collect();
+    // Listed out in full so that new options can't be forgotten. Options for playing on one
+    // machine are left out, as they don't apply online, and so is handling, which each player
+    // picks for themselves
+    let Settings {
+        board_width,
+        board_height,
+        lock_delay,
+        piece_preview_count,
+        starting_level,
+        handling: _,
+        piece_set: _,
+        randomizer,
+        mode,
+        hole_placement,
+        cheese_lines,
+        stack_effect,
+        big,
+        players: _,
+        shared_seed: _,
+        bots: _,
+        bot_move_delay: _,
+        input_delay: _,
+    } = settings;
+    let options = (
+        (board_width, board_height, lock_delay, piece_preview_count),
+        (starting_level, randomizer, mode),
+        (hole_placement, cheese_lines, stack_effect, big),
+    );
+
+    format!("{options:?}{pieces:?}")
+        .bytes()
+        .fold(FNV_OFFSET_BASIS, |hash, byte| {
+            (hash ^ u64::from(byte)).wrapping_mul(FNV_PRIME)
+        })
+}
+
+#[cfg(test)]
+mod tests {
+    use std::{net::SocketAddr, thread::JoinHandle, time::Duration};
+
+    use super::*;
+
+    const SEED: u64 = 1234;
+
+    /// Host a game on a free port, on a thread of its own
+    fn host(settings: &Settings) -> (SocketAddr, JoinHandle<Result<Connection, NetError>>) {
+        let listener = TcpListener::bind("127.0.0.1:0").expect("a free port should be bound");
+        let addr = listener
+            .local_addr()
+            .expect("the listener should have an address");
+        let settings = settings.clone();
+        let host = thread::spawn(move || Connection::accept(&listener, &settings, SEED));
+        (addr, host)
+    }
+
+    /// Say hello to a host directly, and return the message it replied with
+    fn hello(addr: SocketAddr, hello: &str) -> Result<Message, NetError> {
+        let (mut writer, mut reader) = split(TcpStream::connect(addr)?)?;
+        writer.write_all(hello.as_bytes())?;
+        writer.flush()?;
+        read_message(&mut reader)
+    }
+
+    #[test]
+    fn both_sides_agree_on_the_seed_and_swap_handling() {
+        let settings = Settings::default();
+        let (addr, host) = host(&settings);
+        let handling = Handling {
+            das: Duration::from_millis(100),
+            ..settings.handling
+        };
+        let guest_settings = Settings {
+            handling,
+            ..settings.clone()
+        };
+
+        let (guest, seed) =
+            Connection::connect(addr, &guest_settings).expect("the guest should be welcomed");
+        let host = host
+            .join()
+            .expect("the host shouldn't panic")
+            .expect("the host should start the game");
+
+        assert_eq!(seed, SEED);
+        assert_eq!(host.opponent_handling(), handling);
+        assert_eq!(guest.opponent_handling(), settings.handling);
+    }
+
+    #[test]
+    fn different_options_are_rejected() {
+        let (addr, host) = host(&Settings::default());
+        let wider = Settings {
+            board_width: 12,
+            ..Settings::default()
+        };
+
+        assert!(matches!(
+            Connection::connect(addr, &wider),
+            Err(NetError::Rejected(_))
+        ));
+        assert!(matches!(
+            host.join().expect("the host shouldn't panic"),
+            Err(NetError::Rejected(_))
+        ));
+    }
+
+    #[test]
+    fn different_protocol_versions_are_rejected() {
+        let settings = Settings::default();
+        let (addr, host) = host(&settings);
+        let message = serde_json::to_string(&Message::Hello {
+            version: PROTOCOL_VERSION + 1,
+            options: options_fingerprint(&settings),
+            handling: settings.handling,
+        })
+        .expect("a hello should be serialized");
+
+        assert!(matches!(
+            hello(addr, &format!("{message}\n")),
+            Ok(Message::Reject { .. })
+        ));
+        assert!(matches!(
+            host.join().expect("the host shouldn't panic"),
+            Err(NetError::Rejected(_))
+        ));
+    }
+
+    #[test]
+    fn a_malformed_hello_is_an_error() {
+        let (addr, host) = host(&Settings::default());
+
+        // The host hangs up without replying
+        assert!(hello(addr, "{\"type\":\"hello\"\n").is_err());
+        assert!(matches!(
+            host.join().expect("the host shouldn't panic"),
+            Err(NetError::InvalidMessage(_))
+        ));
+    }
+
+    #[test]
+    fn a_line_that_never_ends_is_an_error() {
+        let mut endless = BufReader::new(io::repeat(b' '));
+
+        assert!(matches!(
+            read_message::<Message>(&mut endless),
+            Err(NetError::MessageTooLong)
+        ));
+    }
+}
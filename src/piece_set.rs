@@ -0,0 +1,318 @@
+//! The pieces a game is played with. The seven tetrominoes are built in, and any other set of
+//! pieces (trominoes, pentominoes or completely custom shapes) can be loaded from a TOML or JSON
+//! file
+use std::{collections::HashMap, fs, path::Path, sync::Arc};
+
+use gemini_engine::core::{Colour, Vec2D};
+use serde::Deserialize;
+use thiserror::Error;
+
+use crate::{Block, BlockType, RotationSystem, Tetromino};
+
+#[derive(Debug, Error)]
+pub enum PieceSetError {
+    #[error(transparent)]
+    Io(#[from] std::io::Error),
+    #[error(transparent)]
+    Toml(#[from] toml::de::Error),
+    #[error(transparent)]
+    Json(#[from] serde_json::Error),
+    #[error("piece sets should be `.toml` or `.json` files")]
+    UnknownFormat,
+    #[error("a piece set needs at least one piece")]
+    Empty,
+    #[error("piece `{piece}` {reason}")]
+    InvalidPiece { piece: String, reason: String },
+}
+
+/// Every piece that can be dealt in a game
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct PieceSet {
+    pieces: Vec<Arc<BlockType>>,
+}
+
+impl PieceSet {
+    /// The seven tetrominoes, with the states and kicks of the given rotation system
+    #[must_use]
+    pub fn tetrominoes(rotation_system: RotationSystem) -> Self {
+        let pieces = Tetromino::ALL_VARIANTS
+            .into_iter()
+            .map(|tetromino| {
+                let rotation_states = rotation_system.rotation_states(tetromino);
+                let kicks = transitions(rotation_states.len())
+                    .map(|(from, to)| ((from, to), rotation_system.wall_kicks(tetromino, from, to)))
+                    .filter(|(_, kicks)| !kicks.is_empty())
+                    .collect();
+
+                Arc::new(BlockType {
+                    name: format!("{tetromino:?}"),
+                    colour: tetromino.colour(),
+                    rotation_states,
+                    kicks,
+                    t_spin_upgrade_kick: rotation_system.t_spin_upgrade_kick(tetromino),
+                    spawn_rotation: rotation_system.spawn_rotation(tetromino),
+                    spawn_offset: Vec2D::ZERO,
+                })
+            })
+            .collect();
+
+        Self { pieces }
+    }
+
+    /// Load a piece set from a `.toml` or `.json` file
+    ///
+    /// # Errors
+    /// Returns an error if the file can't be read, isn't valid TOML or JSON or describes invalid
+    /// pieces
+    pub fn load(path: &Path) -> Result<Self, PieceSetError> {
+        let contents = fs::read_to_string(path)?;
+        let file = match path.extension().and_then(|extension| extension.to_str()) {
+            Some("toml") => toml::from_str(&contents)?,
+            Some("json") => serde_json::from_str(&contents)?,
+            _ => return Err(PieceSetError::UnknownFormat),
+        };
+
+        Self::from_file(file)
+    }
+
+    fn from_file(file: PieceSetFile) -> Result<Self, PieceSetError> {
+        if file.pieces.is_empty() {
+            return Err(PieceSetError::Empty);
+        }
+
+        let pieces = file
+            .pieces
+            .into_iter()
+            .map(|piece| piece.into_block_type().map(Arc::new))
+            .collect::<Result<_, _>>()?;
+
+        Ok(Self { pieces })
+    }
+
+    #[must_use]
+    pub fn pieces(&self) -> &[Arc<BlockType>] {
+        &self.pieces
+    }
+
+    /// Returns true if every piece spawns inside a board `board_width` columns wide
+    #[must_use]
+    pub fn fits_board(&self, board_width: usize) -> bool {
+        self.pieces.iter().all(|piece| {
+            Block::spawn(Arc::clone(piece), board_width)
+                .cells()
+                .iter()
+                .all(|cell| (1..=board_width as i64).contains(&cell.x))
+        })
+    }
+
+    /// The lowest row any piece covers when it spawns on a board `board_width` columns wide. Placed
+    /// blocks on or above this row can block pieces from spawning
+    #[must_use]
+    pub fn spawn_area_bottom(&self, board_width: usize) -> i64 {
+        self.pieces
+            .iter()
+            .flat_map(|piece| Block::spawn(Arc::clone(piece), board_width).cells())
+            .map(|cell| cell.y)
+            .max()
+            .unwrap_or_default()
+    }
+
+    /// The number of rows taken up by the tallest piece in its spawn rotation
+    #[must_use]
+    pub fn max_spawn_height(&self) -> usize {
+        self.pieces
+            .iter()
+            .map(|piece| {
+                let rows = Block::new(Arc::clone(piece))
+                    .cells()
+                    .into_iter()
+                    .map(|cell| cell.y);
+                let (min, max) = rows.fold((i64::MAX, i64::MIN), |(min, max), y| {
+                    (min.min(y), max.max(y))
+                });
+                (max - min + 1) as usize
+            })
+            .max()
+            .unwrap_or_default()
+    }
+}
+
+impl Default for PieceSet {
+    fn default() -> Self {
+        Self::tetrominoes(RotationSystem::default())
+    }
+}
+
+/// Every rotation a piece with `state_count` rotation states can make: clockwise, anticlockwise
+/// and 180 degrees from each state
+fn transitions(state_count: usize) -> impl Iterator<Item = (usize, usize)> {
+    (0..state_count).flat_map(move |from| {
+        [1, state_count - 1, 2]
+            .into_iter()
+            .map(move |steps| (from, (from + steps) % state_count))
+            .filter(|(from, to)| from != to)
+    })
+}
+
+const fn to_vec2d((x, y): (i64, i64)) -> Vec2D {
+    Vec2D::new(x, y)
+}
+
+#[derive(Deserialize)]
+#[serde(deny_unknown_fields)]
+struct PieceSetFile {
+    pieces: Vec<PieceDefinition>,
+}
+
+/// A piece as it's written in a piece set file. Cells and offsets are `[x, y]` pairs, with y
+/// pointing down
+#[derive(Deserialize)]
+#[serde(deny_unknown_fields)]
+struct PieceDefinition {
+    name: String,
+    colour: [u8; 3],
+    rotations: Vec<Vec<(i64, i64)>>,
+    /// Rotations without kicks listed here rotate in place
+    #[serde(default)]
+    kicks: Vec<KickDefinition>,
+    #[serde(default)]
+    spawn_rotation: usize,
+    #[serde(default)]
+    spawn_offset: (i64, i64),
+}
+
+#[derive(Deserialize)]
+#[serde(deny_unknown_fields)]
+struct KickDefinition {
+    from: usize,
+    to: usize,
+    offsets: Vec<(i64, i64)>,
+}
+
+impl PieceDefinition {
+    fn into_block_type(self) -> Result<BlockType, PieceSetError> {
+        let invalid = |reason: &str| PieceSetError::InvalidPiece {
+            piece: self.name.clone(),
+            reason: reason.to_string(),
+        };
+
+        let state_count = self.rotations.len();
+        if state_count == 0 || self.rotations.iter().any(Vec::is_empty) {
+            return Err(invalid(
+                "needs at least one rotation with at least one cell",
+            ));
+        }
+        if self
+            .rotations
+            .iter()
+            .any(|cells| cells.len() != self.rotations[0].len())
+        {
+            return Err(invalid("has rotations with different numbers of cells"));
+        }
+        if self.spawn_rotation >= state_count {
+            return Err(invalid("has a spawn rotation it doesn't have"));
+        }
+
+        let mut kicks: HashMap<_, _> = transitions(state_count)
+            .map(|transition| (transition, vec![Vec2D::ZERO]))
+            .collect();
+        for kick in &self.kicks {
+            if kick.from >= state_count || kick.to >= state_count {
+                return Err(invalid("has kicks for a rotation it doesn't have"));
+            }
+            kicks.insert(
+                (kick.from, kick.to),
+                kick.offsets.iter().copied().map(to_vec2d).collect(),
+            );
+        }
+
+        let [r, g, b] = self.colour;
+        Ok(BlockType {
+            colour: Colour::rgb(r, g, b),
+            rotation_states: self
+                .rotations
+                .iter()
+                .map(|cells| cells.iter().copied().map(to_vec2d).collect())
+                .collect(),
+            kicks,
+            // Upgrading Minis is part of SRS, and custom pieces kick however their file says
+            t_spin_upgrade_kick: None,
+            spawn_rotation: self.spawn_rotation,
+            spawn_offset: to_vec2d(self.spawn_offset),
+            name: self.name,
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::path::PathBuf;
+
+    use super::*;
+
+    /// Load a file from the root of the repository
+    fn load(path: &str) -> Result<PieceSet, PieceSetError> {
+        PieceSet::load(&PathBuf::from(env!("CARGO_MANIFEST_DIR")).join(path))
+    }
+
+    fn parse(toml: &str) -> Result<PieceSet, PieceSetError> {
+        PieceSet::from_file(toml::from_str(toml).expect("the test piece set should parse"))
+    }
+
+    #[test]
+    fn the_bundled_piece_sets_load() {
+        let pentominoes = load("piece_sets/pentominoes.toml").expect("the pentominoes should load");
+        let trominoes = load("piece_sets/trominoes.json").expect("the trominoes should load");
+
+        assert_eq!(pentominoes.pieces().len(), 18);
+        assert_eq!(trominoes.pieces().len(), 2);
+        assert!(pentominoes.fits_board(10));
+        assert!(trominoes.fits_board(10));
+    }
+
+    #[test]
+    fn invalid_piece_sets_are_rejected() {
+        assert!(matches!(parse("pieces = []"), Err(PieceSetError::Empty)));
+        assert!(matches!(
+            load("README.md"),
+            Err(PieceSetError::UnknownFormat)
+        ));
+
+        for piece in [
+            "rotations = []",
+            "rotations = [[]]",
+            "rotations = [[[0, 0]], [[0, 0], [1, 0]]]",
+            "rotations = [[[0, 0]]]\nspawn_rotation = 1",
+            "rotations = [[[0, 0]], [[0, 1]]]\nkicks = [{ from = 0, to = 2, offsets = [] }]",
+        ] {
+            let toml = format!("[[pieces]]\nname = \"X\"\ncolour = [0, 0, 0]\n{piece}");
+            let error = parse(&toml).expect_err(&toml);
+            assert!(
+                matches!(&error, PieceSetError::InvalidPiece { piece, .. } if piece == "X"),
+                "{error}"
+            );
+        }
+    }
+
+    #[test]
+    fn the_tetrominoes_fit_the_narrowest_board() {
+        for rotation_system in [
+            RotationSystem::Srs,
+            RotationSystem::SrsPlus,
+            RotationSystem::Ars,
+        ] {
+            let tetrominoes = PieceSet::tetrominoes(rotation_system);
+            assert!(tetrominoes.fits_board(4));
+            assert!(!tetrominoes.fits_board(3));
+        }
+
+        // The NRS I piece spawns left of centre, so it needs a column more
+        let nrs = PieceSet::tetrominoes(RotationSystem::Nrs);
+        assert!(!nrs.fits_board(4));
+        assert!(nrs.fits_board(5));
+        // The I pentomino is five cells wide
+        let pentominoes = load("piece_sets/pentominoes.toml").expect("the pentominoes should load");
+        assert!(!pentominoes.fits_board(4));
+        assert!(pentominoes.fits_board(5));
+    }
+}
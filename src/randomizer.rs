@@ -0,0 +1,389 @@
+//! Strategies for picking the order pieces are dealt in. Every randomizer draws from the game's
+//! seeded RNG, so a seed and a [`RandomizerKind`] are enough to reproduce a game's pieces
+use std::{collections::VecDeque, str::FromStr};
+
+use rand::{seq::SliceRandom, Rng, RngCore};
+
+use crate::PieceSet;
+
+/// The pieces kept out of the start of classic and TGM games, since they can't be placed without
+/// leaving a hole on an empty board
+const AWKWARD_FIRST_PIECES: [&str; 3] = ["S", "Z", "O"];
+
+/// Picks the next piece to deal
+pub trait Randomizer {
+    /// The index of the next piece in the piece set
+    fn next_piece(&mut self, rng: &mut dyn RngCore) -> usize;
+
+    /// A copy of the randomizer in its current state, so a copied game deals the same pieces
+    fn clone_box(&self) -> Box<dyn Randomizer>;
+}
+
+impl Clone for Box<dyn Randomizer> {
+    fn clone(&self) -> Self {
+        self.clone_box()
+    }
+}
+
+/// The randomizer a game is played with
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum RandomizerKind {
+    /// Deals every piece once in a shuffled bag before starting the next bag
+    #[default]
+    Bag,
+    /// A bag with two of every piece
+    DoubleBag,
+    /// Every piece is equally likely every time
+    Random,
+    /// The Grand Master randomizer: a piece in the last four dealt is rerolled up to six times
+    Tgm,
+    /// The NES randomizer: a repeat of the last piece is rerolled once
+    Nes,
+    /// A 7-bag whose first piece is never S, Z or O. Only the first piece is held to this, so after
+    /// a first piece that was fine anyway it deals the same pieces as a 7-bag with the same seed
+    Classic,
+}
+
+impl RandomizerKind {
+    pub const ALL: [Self; 6] = [
+        Self::Bag,
+        Self::DoubleBag,
+        Self::Random,
+        Self::Tgm,
+        Self::Nes,
+        Self::Classic,
+    ];
+
+    /// The name the randomizer is selected by
+    #[must_use]
+    pub const fn name(self) -> &'static str {
+        match self {
+            Self::Bag => "7-bag",
+            Self::DoubleBag => "14-bag",
+            Self::Random => "random",
+            Self::Tgm => "tgm",
+            Self::Nes => "nes",
+            Self::Classic => "classic",
+        }
+    }
+
+    /// Create the randomizer for a game played with `piece_set`
+    #[must_use]
+    pub fn build(self, piece_set: &PieceSet) -> Box<dyn Randomizer> {
+        let piece_count = piece_set.pieces().len();
+        match self {
+            Self::Bag => Box::new(BagRandomizer::new(piece_count, 1, vec![])),
+            Self::DoubleBag => Box::new(BagRandomizer::new(piece_count, 2, vec![])),
+            Self::Random => Box::new(PureRandomizer { piece_count }),
+            Self::Tgm => Box::new(TgmRandomizer::new(piece_set)),
+            Self::Nes => Box::new(NesRandomizer {
+                piece_count,
+                last_piece: None,
+            }),
+            Self::Classic => Box::new(BagRandomizer::new(
+                piece_count,
+                1,
+                pieces_named(piece_set, &AWKWARD_FIRST_PIECES),
+            )),
+        }
+    }
+}
+
+impl FromStr for RandomizerKind {
+    type Err = UnknownRandomizer;
+
+    fn from_str(name: &str) -> Result<Self, Self::Err> {
+        Self::ALL
+            .into_iter()
+            .find(|kind| kind.name() == name)
+            .ok_or(UnknownRandomizer)
+    }
+}
+
+/// Returned when parsing a name that doesn't belong to any [`RandomizerKind`]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct UnknownRandomizer;
+
+/// The indices of the pieces with any of the given names
+fn pieces_named(piece_set: &PieceSet, names: &[&str]) -> Vec<usize> {
+    piece_set
+        .pieces()
+        .iter()
+        .enumerate()
+        .filter(|(_, piece)| names.contains(&piece.name.as_str()))
+        .map(|(i, _)| i)
+        .collect()
+}
+
+/// Shuffles `copies` of every piece into a bag and deals them all before refilling it
+#[derive(Clone)]
+struct BagRandomizer {
+    piece_count: usize,
+    copies: usize,
+    bag: Vec<usize>,
+    /// Pieces the very first piece of the game can't be. The rest of the first bag is left as it
+    /// was shuffled
+    avoid_first: Vec<usize>,
+}
+
+impl BagRandomizer {
+    fn new(piece_count: usize, copies: usize, avoid_first: Vec<usize>) -> Self {
+        Self {
+            piece_count,
+            copies,
+            bag: vec![],
+            // Nothing could be dealt first if every piece was avoided
+            avoid_first: if avoid_first.len() < piece_count {
+                avoid_first
+            } else {
+                vec![]
+            },
+        }
+    }
+}
+
+impl Randomizer for BagRandomizer {
+    fn next_piece(&mut self, rng: &mut dyn RngCore) -> usize {
+        if self.bag.is_empty() {
+            self.bag = (0..self.piece_count)
+                .flat_map(|piece| std::iter::repeat_n(piece, self.copies))
+                .collect();
+            self.bag.shuffle(rng);
+
+            // Pieces are dealt from the end of the bag
+            while self
+                .bag
+                .last()
+                .is_some_and(|piece| self.avoid_first.contains(piece))
+            {
+                self.bag.shuffle(rng);
+            }
+            self.avoid_first.clear();
+        }
+
+        self.bag.pop().unwrap_or_default()
+    }
+
+    fn clone_box(&self) -> Box<dyn Randomizer> {
+        Box::new(self.clone())
+    }
+}
+
+#[derive(Clone)]
+struct PureRandomizer {
+    piece_count: usize,
+}
+
+impl Randomizer for PureRandomizer {
+    fn next_piece(&mut self, rng: &mut dyn RngCore) -> usize {
+        rng.gen_range(0..self.piece_count)
+    }
+
+    fn clone_box(&self) -> Box<dyn Randomizer> {
+        Box::new(self.clone())
+    }
+}
+
+/// Rolls one extra possibility as a "reroll" result, and rerolls repeats of the last piece too.
+/// The second roll is always kept
+#[derive(Clone)]
+struct NesRandomizer {
+    piece_count: usize,
+    last_piece: Option<usize>,
+}
+
+impl Randomizer for NesRandomizer {
+    fn next_piece(&mut self, rng: &mut dyn RngCore) -> usize {
+        let roll = rng.gen_range(0..=self.piece_count);
+        let piece = if roll == self.piece_count || Some(roll) == self.last_piece {
+            rng.gen_range(0..self.piece_count)
+        } else {
+            roll
+        };
+
+        self.last_piece = Some(piece);
+        piece
+    }
+
+    fn clone_box(&self) -> Box<dyn Randomizer> {
+        Box::new(self.clone())
+    }
+}
+
+/// The number of pieces the TGM randomizer remembers
+const TGM_HISTORY_LEN: usize = 4;
+/// The number of times the TGM randomizer rolls before giving up and dealing a repeat
+const TGM_ROLLS: usize = 6;
+
+/// Rolls up to [`TGM_ROLLS`] times for a piece that isn't in the history of the last
+/// [`TGM_HISTORY_LEN`] pieces. The history starts out full of S and Z, and the first piece is never
+/// S, Z or O
+#[derive(Clone)]
+struct TgmRandomizer {
+    piece_count: usize,
+    history: VecDeque<usize>,
+    /// Pieces the very first piece of the game can't be
+    avoid_first: Vec<usize>,
+}
+
+impl TgmRandomizer {
+    fn new(piece_set: &PieceSet) -> Self {
+        let piece_count = piece_set.pieces().len();
+        let avoid_first = pieces_named(piece_set, &AWKWARD_FIRST_PIECES);
+
+        Self {
+            piece_count,
+            history: pieces_named(piece_set, &["Z", "S"])
+                .into_iter()
+                .cycle()
+                .take(TGM_HISTORY_LEN)
+                .collect(),
+            avoid_first: if avoid_first.len() < piece_count {
+                avoid_first
+            } else {
+                vec![]
+            },
+        }
+    }
+}
+
+impl Randomizer for TgmRandomizer {
+    fn next_piece(&mut self, rng: &mut dyn RngCore) -> usize {
+        let mut piece = rng.gen_range(0..self.piece_count);
+        if self.avoid_first.is_empty() {
+            for _ in 1..TGM_ROLLS {
+                if !self.history.contains(&piece) {
+                    break;
+                }
+                piece = rng.gen_range(0..self.piece_count);
+            }
+        } else {
+            while self.avoid_first.contains(&piece) {
+                piece = rng.gen_range(0..self.piece_count);
+            }
+            self.avoid_first.clear();
+        }
+
+        self.history.push_back(piece);
+        if self.history.len() > TGM_HISTORY_LEN {
+            self.history.pop_front();
+        }
+        piece
+    }
+
+    fn clone_box(&self) -> Box<dyn Randomizer> {
+        Box::new(self.clone())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use rand::{rngs::StdRng, SeedableRng};
+
+    use super::*;
+    use crate::RotationSystem;
+
+    /// The names of the first `count` pieces dealt by `kind` with `seed`
+    fn deal(kind: RandomizerKind, seed: u64, count: usize) -> Vec<String> {
+        let piece_set = PieceSet::tetrominoes(RotationSystem::Srs);
+        let mut randomizer = kind.build(&piece_set);
+        let mut rng = StdRng::seed_from_u64(seed);
+        (0..count)
+            .map(|_| {
+                piece_set.pieces()[randomizer.next_piece(&mut rng)]
+                    .name
+                    .clone()
+            })
+            .collect()
+    }
+
+    /// The share of pieces that are the same as the piece `distance` before them
+    fn repeat_rate(pieces: &[String], distance: usize) -> f64 {
+        let repeats = pieces
+            .windows(distance + 1)
+            .filter(|window| window[0] == window[distance])
+            .count();
+        repeats as f64 / (pieces.len() - distance) as f64
+    }
+
+    fn sorted(pieces: &[String]) -> Vec<&str> {
+        let mut pieces: Vec<_> = pieces.iter().map(String::as_str).collect();
+        pieces.sort_unstable();
+        pieces
+    }
+
+    #[test]
+    fn every_bag_holds_every_piece() {
+        let first_bag = deal(RandomizerKind::Bag, 0, 7);
+        let seven = sorted(&first_bag);
+        for seed in 0..20 {
+            for bag in deal(RandomizerKind::Bag, seed, 70).chunks(7) {
+                assert_eq!(sorted(bag), seven);
+            }
+
+            let fourteen: Vec<_> = seven.iter().flat_map(|&piece| [piece; 2]).collect();
+            for bag in deal(RandomizerKind::DoubleBag, seed, 70).chunks(14) {
+                assert_eq!(sorted(bag), fourteen);
+            }
+        }
+    }
+
+    #[test]
+    fn classic_and_tgm_never_start_with_s_z_or_o() {
+        for kind in [RandomizerKind::Classic, RandomizerKind::Tgm] {
+            for seed in 0..200 {
+                let first = &deal(kind, seed, 1)[0];
+                assert!(
+                    !AWKWARD_FIRST_PIECES.contains(&first.as_str()),
+                    "{seed}: {first}"
+                );
+            }
+        }
+    }
+
+    #[test]
+    fn classic_only_changes_the_first_bag_when_it_starts_badly() {
+        let mut reshuffled = 0;
+        for seed in 0..50 {
+            let bag = deal(RandomizerKind::Bag, seed, 28);
+            let classic = deal(RandomizerKind::Classic, seed, 28);
+            assert_eq!(sorted(&classic[..7]), sorted(&bag[..7]));
+
+            if AWKWARD_FIRST_PIECES.contains(&bag[0].as_str()) {
+                reshuffled += 1;
+            } else {
+                assert_eq!(classic, bag);
+            }
+        }
+        assert!(reshuffled > 0);
+    }
+
+    #[test]
+    fn tgm_rerolls_pieces_from_the_last_four() {
+        let pieces = deal(RandomizerKind::Tgm, 3, 10_000);
+        // Anything in the last four pieces is rerolled up to six times, so repeats are rare
+        for distance in 1..=TGM_HISTORY_LEN {
+            assert!(repeat_rate(&pieces, distance) < 0.05);
+        }
+        // The piece before those is one of the few left to pick from
+        assert!(repeat_rate(&pieces, TGM_HISTORY_LEN + 1) > 0.2);
+    }
+
+    #[test]
+    fn nes_rerolls_a_repeat_only_once() {
+        let pieces = deal(RandomizerKind::Nes, 3, 10_000);
+        // A repeat is rolled 1 in 8 times, and the reroll repeats 1 in 7 times, with the same for
+        // the reroll result. Never rerolling would repeat 1 in 7 times, and rerolling until
+        // something else came up would never repeat
+        let rate = repeat_rate(&pieces, 1);
+        assert!((0.01..0.07).contains(&rate), "{rate}");
+    }
+
+    #[test]
+    fn kinds_are_parsed_by_name() {
+        for kind in RandomizerKind::ALL {
+            assert_eq!(kind.name().parse(), Ok(kind));
+        }
+        assert_eq!("8-bag".parse::<RandomizerKind>(), Err(UnknownRandomizer));
+    }
+}
@@ -0,0 +1,108 @@
+//! Drawing the board and blocks of Big mode, where every cell is blown up to a square of cells
+use gemini_engine::core::{CanDraw, Canvas, ColChar, Vec2D};
+
+/// Draws an element with every cell blown up to a `scale` by `scale` square. The squares line up
+/// with a board drawn at the same scale, so the walls stay one cell thick
+pub struct Scaled<'a, E> {
+    pub element: &'a E,
+    pub scale: i64,
+}
+
+impl<E: CanDraw> CanDraw for Scaled<'_, E> {
+    fn draw_to(&self, canvas: &mut impl Canvas) {
+        self.element.draw_to(&mut ScaledCanvas {
+            canvas,
+            scale: self.scale,
+        });
+    }
+}
+
+/// Plots every cell of the field as a `scale` by `scale` square
+pub struct ScaledCanvas<'a, C> {
+    pub canvas: &'a mut C,
+    pub scale: i64,
+}
+
+impl<C: Canvas> Canvas for ScaledCanvas<'_, C> {
+    fn plot(&mut self, pos: Vec2D, c: ColChar) {
+        // Column 1 is the first column right of the wall at every scale
+        let corner = Vec2D::new((pos.x - 1) * self.scale + 1, pos.y * self.scale);
+        for y in 0..self.scale {
+            for x in 0..self.scale {
+                self.canvas.plot(corner + Vec2D::new(x, y), c);
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{Board, Engine, Input, Settings};
+
+    /// Remembers where everything was plotted, in order
+    #[derive(Default)]
+    struct Screen(Vec<Vec2D>);
+
+    impl Canvas for Screen {
+        fn plot(&mut self, pos: Vec2D, _: ColChar) {
+            self.0.push(pos);
+        }
+    }
+
+    fn drawn(element: &impl CanDraw, scale: i64) -> Vec<Vec2D> {
+        let mut screen = Screen::default();
+        Scaled { element, scale }.draw_to(&mut screen);
+        screen.0
+    }
+
+    fn drawn_stack(board: &Board, scale: i64) -> Vec<Vec2D> {
+        let mut screen = Screen::default();
+        let mut canvas = ScaledCanvas {
+            canvas: &mut screen,
+            scale,
+        };
+        for (pos, colour) in board.cells() {
+            canvas.plot(pos, colour);
+        }
+        screen.0
+    }
+
+    #[test]
+    fn one_shift_moves_a_big_piece_two_columns_on_screen() {
+        let settings = Settings {
+            big: true,
+            ..Settings::default()
+        };
+        let mut engine = Engine::new(&settings, 0);
+        let before = drawn(engine.active_block(), engine.scale());
+        assert_eq!(before.len(), engine.active_block().cells().len() * 4);
+
+        engine.apply_input(Input::ShiftLeft);
+
+        let after = drawn(engine.active_block(), engine.scale());
+        let moved: Vec<_> = before.iter().map(|&pos| pos - Vec2D::new(2, 0)).collect();
+        assert_eq!(after, moved);
+    }
+
+    #[test]
+    fn one_line_clear_takes_out_two_rows_on_screen() {
+        // The field of a big 10x20 board
+        let mut board = Board::new(5, 10);
+        for x in 1..=5 {
+            board.fill(Vec2D::new(x, 9), ColChar::SOLID);
+        }
+        board.fill(Vec2D::new(1, 8), ColChar::SOLID);
+        assert_eq!(drawn_stack(&board, 2).len(), 6 * 4);
+
+        assert_eq!(board.clear_filled_lines(), 1);
+
+        // The cell left over falls a whole cell, two rows on screen, to the bottom
+        let mut after = drawn_stack(&board, 2);
+        after.sort_unstable_by_key(|pos| (pos.y, pos.x));
+        assert_eq!(
+            after,
+            [(1, 18), (2, 18), (1, 19), (2, 19)].map(|(x, y)| Vec2D::new(x, y))
+        );
+    }
+}
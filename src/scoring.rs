@@ -0,0 +1,277 @@
+//! Guideline scoring. Line clears and T-spins are worth more at higher levels, consecutive
+//! difficult clears earn a back-to-back bonus and clearing lines with several pieces in a row
+//! builds a combo
+
+/// Points per row for soft dropping
+pub const SOFT_DROP_SCORE: i64 = 1;
+/// Points per row for hard dropping
+pub const HARD_DROP_SCORE: i64 = 2;
+/// Points per combo count, multiplied by the level
+pub const COMBO_SCORE: i64 = 50;
+
+/// A T piece spun into a slot it couldn't have been moved into otherwise
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum TSpin {
+    /// Only one of the corners the T points towards is filled
+    Mini,
+    Full,
+}
+
+/// The lines cleared by placing a piece
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct LineClear {
+    pub lines: i64,
+    pub t_spin: Option<TSpin>,
+    /// Whether the clear left the board completely empty
+    pub perfect_clear: bool,
+}
+
+impl LineClear {
+    /// The points the clear is worth at level 1 and its name, or `None` if it isn't worth
+    /// anything. Pieces bigger than tetrominoes can clear more than 4 lines at once, which are
+    /// worth 200 points a line
+    #[must_use]
+    pub fn base_score(self) -> Option<(i64, String)> {
+        let lines = self.lines;
+        match self.t_spin {
+            None if lines == 0 => None,
+            None => {
+                let score = match lines {
+                    1 => 100,
+                    2 => 300,
+                    3 => 500,
+                    _ => 200 * lines,
+                };
+                Some((score, clear_name(lines)))
+            }
+            Some(TSpin::Mini) => Some((100 << lines, spin_name("T-Spin Mini", lines))),
+            Some(TSpin::Full) => Some((400 * (lines + 1), spin_name("T-Spin", lines))),
+        }
+    }
+
+    /// The bonus for emptying the board at level 1, on top of the score for the clear itself
+    #[must_use]
+    pub const fn perfect_clear_bonus(self, is_back_to_back: bool) -> i64 {
+        match self.lines {
+            4.. if is_back_to_back => 3200,
+            4.. => 2000,
+            3 => 1800,
+            2 => 1200,
+            _ => 800,
+        }
+    }
+
+    /// Tetrises (or bigger) and T-spins that clear lines are difficult, and keep a back-to-back
+    /// chain going
+    #[must_use]
+    pub const fn is_difficult(self) -> bool {
+        self.lines >= 4 || (self.t_spin.is_some() && self.lines > 0)
+    }
+}
+
+fn clear_name(lines: i64) -> String {
+    match lines {
+        1 => String::from("Single"),
+        2 => String::from("Double"),
+        3 => String::from("Triple"),
+        4 => String::from("Tetris"),
+        _ => format!("{lines} Lines"),
+    }
+}
+
+fn spin_name(spin: &str, lines: i64) -> String {
+    if lines == 0 {
+        spin.to_string()
+    } else {
+        format!("{spin} {}", clear_name(lines))
+    }
+}
+
+/// Keeps the score of a game, along with the back-to-back chain and combo that bonuses depend on
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct Scoring {
+    score: i64,
+    /// The number of difficult clears in a row, without an easy clear in between
+    back_to_back: u32,
+    /// The number of pieces in a row that cleared lines, minus one. `None` once a piece is placed
+    /// without clearing anything
+    combo: Option<u32>,
+}
+
+impl Scoring {
+    #[must_use]
+    pub const fn new() -> Self {
+        Self {
+            score: 0,
+            back_to_back: 0,
+            combo: None,
+        }
+    }
+
+    #[must_use]
+    pub const fn score(&self) -> i64 {
+        self.score
+    }
+
+    /// The number of back-to-back bonuses in the current chain
+    #[must_use]
+    pub const fn back_to_back(&self) -> u32 {
+        self.back_to_back.saturating_sub(1)
+    }
+
+    /// The current combo, or 0 if the last piece didn't clear any lines
+    #[must_use]
+    pub const fn combo(&self) -> u32 {
+        match self.combo {
+            Some(combo) => combo,
+            None => 0,
+        }
+    }
+
+    /// Award points for dropping a piece by `rows` rows
+    pub const fn add_drop(&mut self, rows: i64, points_per_row: i64) {
+        self.score += rows * points_per_row;
+    }
+
+    /// Score a placed piece at the given level, returning the alerts it earned
+    pub fn award(&mut self, clear: LineClear, level: u32) -> Vec<String> {
+        let level = i64::from(level);
+        self.combo = if clear.lines > 0 {
+            Some(self.combo.map_or(0, |combo| combo + 1))
+        } else {
+            None
+        };
+
+        let Some((base_score, name)) = clear.base_score() else {
+            return vec![];
+        };
+        let mut points = base_score * level;
+        let mut alerts = vec![format!("{name}!")];
+
+        // T-spins that don't clear anything neither continue nor break a back-to-back chain
+        if clear.is_difficult() {
+            self.back_to_back += 1;
+            if self.back_to_back > 1 {
+                points = points * 3 / 2;
+                alerts.push(format!("Back-to-Back x{}", self.back_to_back()));
+            }
+        } else if clear.lines > 0 {
+            self.back_to_back = 0;
+        }
+
+        if clear.perfect_clear {
+            let is_back_to_back = self.back_to_back > 1;
+            points += clear.perfect_clear_bonus(is_back_to_back) * level;
+            alerts.push(String::from(if is_back_to_back {
+                "Back-to-Back Perfect Clear!"
+            } else {
+                "Perfect Clear!"
+            }));
+        }
+
+        if let Some(combo) = self.combo.filter(|&combo| combo > 0) {
+            points += COMBO_SCORE * i64::from(combo) * level;
+            alerts.push(format!("{combo} Combo!"));
+        }
+
+        self.score += points;
+        alerts
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const fn clear(lines: i64) -> LineClear {
+        LineClear {
+            lines,
+            t_spin: None,
+            perfect_clear: false,
+        }
+    }
+
+    /// Award `clear`, returning the points it earned
+    fn points(scoring: &mut Scoring, clear: LineClear, level: u32) -> i64 {
+        let before = scoring.score();
+        scoring.award(clear, level);
+        scoring.score() - before
+    }
+
+    #[test]
+    fn clears_are_multiplied_by_the_level() {
+        let mut scoring = Scoring::new();
+        assert_eq!(points(&mut scoring, clear(1), 3), 300);
+        scoring.award(clear(0), 3);
+        assert_eq!(points(&mut scoring, clear(4), 2), 1600);
+        scoring.award(clear(0), 2);
+        let t_spin_double = LineClear {
+            t_spin: Some(TSpin::Full),
+            ..clear(2)
+        };
+        // Back-to-back with the Tetris
+        assert_eq!(points(&mut scoring, t_spin_double, 1), 1200 * 3 / 2);
+    }
+
+    #[test]
+    fn difficult_clears_in_a_row_earn_back_to_back() {
+        let mut scoring = Scoring::new();
+        assert_eq!(points(&mut scoring, clear(4), 1), 800);
+        scoring.award(clear(0), 1);
+        let alerts = scoring.award(clear(4), 1);
+        assert_eq!(alerts, ["Tetris!", "Back-to-Back x1"]);
+        assert_eq!(scoring.score(), 800 + 1200);
+
+        // A T-spin that doesn't clear anything keeps the chain going
+        scoring.award(
+            LineClear {
+                t_spin: Some(TSpin::Full),
+                ..clear(0)
+            },
+            1,
+        );
+        assert_eq!(points(&mut scoring, clear(4), 1), 1200);
+        assert_eq!(scoring.back_to_back(), 2);
+
+        // An easy clear breaks it
+        scoring.award(clear(1), 1);
+        scoring.award(clear(0), 1);
+        assert_eq!(scoring.back_to_back(), 0);
+        assert_eq!(points(&mut scoring, clear(4), 1), 800);
+    }
+
+    #[test]
+    fn clearing_with_pieces_in_a_row_builds_a_combo() {
+        let mut scoring = Scoring::new();
+        assert_eq!(points(&mut scoring, clear(1), 2), 200);
+        assert_eq!(scoring.combo(), 0);
+        assert_eq!(points(&mut scoring, clear(1), 2), 200 + COMBO_SCORE * 2);
+        let alerts = scoring.award(clear(2), 2);
+        assert_eq!(alerts, ["Double!", "2 Combo!"]);
+        assert_eq!(scoring.combo(), 2);
+
+        scoring.award(clear(0), 2);
+        assert_eq!(scoring.combo(), 0);
+        assert_eq!(points(&mut scoring, clear(1), 2), 200);
+    }
+
+    #[test]
+    fn perfect_clears_earn_a_bonus_on_top_of_the_clear() {
+        let mut scoring = Scoring::new();
+        let perfect = |lines| LineClear {
+            perfect_clear: true,
+            ..clear(lines)
+        };
+        assert_eq!(points(&mut scoring, perfect(1), 2), (100 + 800) * 2);
+        scoring.award(clear(0), 1);
+        assert_eq!(points(&mut scoring, perfect(4), 1), 800 + 2000);
+        scoring.award(clear(0), 1);
+
+        let alerts = scoring.award(perfect(4), 1);
+        assert_eq!(
+            alerts,
+            ["Tetris!", "Back-to-Back x1", "Back-to-Back Perfect Clear!"]
+        );
+        assert_eq!(scoring.score(), 1800 + 2800 + 1200 + 3200);
+    }
+}
@@ -0,0 +1,97 @@
+use std::time::Duration;
+
+use crate::{
+    game_mode::GameMode, garbage::HolePlacement, handling::Handling, lock_delay::LockDelay,
+    randomizer::RandomizerKind, stack_effect::StackEffect, PieceSet,
+};
+
+/// Everything about a game that is decided before it starts
+#[derive(Debug, Clone, PartialEq)]
+pub struct Settings {
+    /// The number of playable columns
+    pub board_width: usize,
+    /// The number of playable rows
+    pub board_height: usize,
+    /// How long a block can rest on the floor before it is placed
+    pub lock_delay: LockDelay,
+    /// The number of upcoming pieces shown in the queue
+    pub piece_preview_count: usize,
+    /// The level the game starts at. Gravity gets faster with every level
+    pub starting_level: u32,
+    /// How held shifts and soft drops repeat
+    pub handling: Handling,
+    /// The pieces that are dealt, along with how they rotate
+    pub piece_set: PieceSet,
+    /// How the order pieces are dealt in is picked
+    pub randomizer: RandomizerKind,
+    /// The goal of the game
+    pub mode: GameMode,
+    /// Where the holes go in garbage rows
+    pub hole_placement: HolePlacement,
+    /// The number of garbage rows to dig through in a [`GameMode::CheeseRace`]
+    pub cheese_lines: u32,
+    /// How the placed blocks are drawn
+    pub stack_effect: StackEffect,
+    /// Play on a field with half as many rows and columns, drawn with every cell at double size
+    pub big: bool,
+    /// The number of people playing on the same keyboard, each on a board of their own
+    pub players: usize,
+    /// Deal every player the same pieces, instead of giving each of them a seed of their own
+    pub shared_seed: bool,
+    /// The number of bots to fight in a [`GameMode::Battle`]
+    pub bots: usize,
+    /// How long bots wait between inputs. Lower is harder
+    pub bot_move_delay: Duration,
+    /// The number of frames inputs are held back in an online game before they're played, giving
+    /// them time to reach the other player
+    pub input_delay: u32,
+}
+
+impl Default for Settings {
+    fn default() -> Self {
+        Self {
+            board_width: 10,
+            board_height: 20,
+            lock_delay: LockDelay::default(),
+            piece_preview_count: 3,
+            starting_level: 1,
+            handling: Handling::default(),
+            piece_set: PieceSet::default(),
+            randomizer: RandomizerKind::default(),
+            mode: GameMode::default(),
+            hole_placement: HolePlacement::default(),
+            cheese_lines: 18,
+            stack_effect: StackEffect::default(),
+            big: false,
+            players: 1,
+            shared_seed: false,
+            bots: 1,
+            bot_move_delay: Duration::from_millis(150),
+            input_delay: 0,
+        }
+    }
+}
+
+impl Settings {
+    /// The number of characters each cell of the field is drawn as, across and down
+    #[must_use]
+    pub const fn scale(&self) -> usize {
+        if self.big {
+            2
+        } else {
+            1
+        }
+    }
+
+    /// The number of columns pieces actually move through, once Big mode is taken into account
+    #[must_use]
+    pub const fn field_width(&self) -> usize {
+        self.board_width / self.scale()
+    }
+
+    /// The number of rows pieces actually move through, once Big mode is taken into account
+    #[must_use]
+    pub const fn field_height(&self) -> usize {
+        self.board_height / self.scale()
+    }
+}
@@ -0,0 +1,199 @@
+//! Ways of hiding the stack from the player. These only change how the board is drawn, so hidden
+//! cells still collide as usual
+use std::{str::FromStr, time::Duration};
+
+use gemini_engine::core::{CanDraw, Canvas, ColChar, Colour, Modifier, Vec2D};
+
+use crate::{engine::FRAME_DURATION, scaling::ScaledCanvas, Board};
+
+/// How long a placed cell stays visible in [`StackEffect::Invisible`]
+pub const INVISIBLE_DELAY: Duration = Duration::from_secs(1);
+/// How long a placed cell takes to fade away completely in [`StackEffect::Fading`]
+pub const FADE_DURATION: Duration = Duration::from_secs(4);
+/// The number of darker colours a cell goes through before it disappears
+const FADE_STEPS: u32 = 4;
+/// How long the whole stack is shown for after a line clear
+pub const LINE_CLEAR_FLASH: Duration = Duration::from_millis(250);
+
+/// How the stack is drawn
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum StackEffect {
+    /// Every placed cell is always shown
+    #[default]
+    Visible,
+    /// Placed cells disappear after [`INVISIBLE_DELAY`]
+    Invisible,
+    /// Placed cells get darker and darker until they disappear after [`FADE_DURATION`]
+    Fading,
+}
+
+impl StackEffect {
+    pub const ALL: [Self; 3] = [Self::Visible, Self::Invisible, Self::Fading];
+
+    /// The name the effect is selected by
+    #[must_use]
+    pub const fn name(self) -> &'static str {
+        match self {
+            Self::Visible => "visible",
+            Self::Invisible => "invisible",
+            Self::Fading => "fading",
+        }
+    }
+
+    /// The colour a cell placed `age` ago is drawn in, or `None` if it's hidden
+    #[must_use]
+    pub fn apply(self, colour: ColChar, age: Duration) -> Option<ColChar> {
+        match self {
+            Self::Visible => Some(colour),
+            Self::Invisible => (age < INVISIBLE_DELAY).then_some(colour),
+            Self::Fading => {
+                let step = (age.as_secs_f64() / FADE_DURATION.as_secs_f64() * f64::from(FADE_STEPS))
+                    as u32;
+                (step < FADE_STEPS).then(|| darken(colour, FADE_STEPS - step, FADE_STEPS))
+            }
+        }
+    }
+}
+
+impl FromStr for StackEffect {
+    type Err = UnknownStackEffect;
+
+    fn from_str(name: &str) -> Result<Self, Self::Err> {
+        Self::ALL
+            .into_iter()
+            .find(|effect| effect.name() == name)
+            .ok_or(UnknownStackEffect)
+    }
+}
+
+/// Returned when parsing a name that doesn't belong to any [`StackEffect`]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct UnknownStackEffect;
+
+/// Scale the colour of `colour` to `numerator / denominator` of its brightness. Characters without
+/// an RGB colour are left as they are
+fn darken(colour: ColChar, numerator: u32, denominator: u32) -> ColChar {
+    match colour.modifier {
+        Modifier::Colour(Colour { r, g, b }) => {
+            let scale = |channel: u8| (u32::from(channel) * numerator / denominator) as u8;
+            colour.with_colour(Colour::rgb(scale(r), scale(g), scale(b)))
+        }
+        _ => colour,
+    }
+}
+
+/// The board as the player sees it with a [`StackEffect`] applied
+pub struct StackView<'a> {
+    pub board: &'a Board,
+    pub effect: StackEffect,
+    /// The current frame, to tell how long ago each cell was placed
+    pub frame: u64,
+    /// Show every cell regardless of the effect, like just after a line clear
+    pub flash: bool,
+    /// The number of characters each cell is drawn as, across and down
+    pub scale: i64,
+}
+
+impl StackView<'_> {
+    /// Every cell of the board that can be seen, in the colour it's drawn in
+    pub fn cells(&self) -> impl Iterator<Item = (Vec2D, ColChar)> + '_ {
+        self.board.cells().filter_map(|(pos, colour)| {
+            if self.flash {
+                return Some((pos, colour));
+            }
+
+            let placed_at = self.board.placed_at(pos).unwrap_or(self.frame);
+            let frames = self.frame.saturating_sub(placed_at);
+            let age = FRAME_DURATION * u32::try_from(frames).unwrap_or(u32::MAX);
+            self.effect.apply(colour, age).map(|colour| (pos, colour))
+        })
+    }
+}
+
+impl CanDraw for StackView<'_> {
+    fn draw_to(&self, canvas: &mut impl Canvas) {
+        self.board.draw_walls(canvas, self.scale);
+
+        let mut canvas = ScaledCanvas {
+            canvas,
+            scale: self.scale,
+        };
+        for (pos, colour) in self.cells() {
+            canvas.plot(pos, colour);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const ORANGE: ColChar = ColChar::SOLID.with_colour(Colour::rgb(200, 100, 40));
+
+    fn millis(millis: u64) -> Duration {
+        Duration::from_millis(millis)
+    }
+
+    #[test]
+    fn visible_stacks_are_always_shown() {
+        assert_eq!(StackEffect::Visible.apply(ORANGE, millis(0)), Some(ORANGE));
+        assert_eq!(
+            StackEffect::Visible.apply(ORANGE, Duration::MAX),
+            Some(ORANGE)
+        );
+    }
+
+    #[test]
+    fn invisible_cells_disappear_after_the_delay() {
+        let effect = StackEffect::Invisible;
+        assert_eq!(
+            effect.apply(ORANGE, INVISIBLE_DELAY.saturating_sub(millis(1))),
+            Some(ORANGE)
+        );
+        assert_eq!(effect.apply(ORANGE, INVISIBLE_DELAY), None);
+    }
+
+    #[test]
+    fn fading_cells_get_darker_until_they_disappear() {
+        let effect = StackEffect::Fading;
+        let fade_step = FADE_DURATION / FADE_STEPS;
+        assert_eq!(effect.apply(ORANGE, millis(0)), Some(ORANGE));
+        assert_eq!(
+            effect.apply(ORANGE, fade_step),
+            Some(ColChar::SOLID.with_colour(Colour::rgb(150, 75, 30)))
+        );
+        assert_eq!(
+            effect.apply(ORANGE, FADE_DURATION.saturating_sub(millis(1))),
+            Some(ColChar::SOLID.with_colour(Colour::rgb(50, 25, 10)))
+        );
+        assert_eq!(effect.apply(ORANGE, FADE_DURATION), None);
+
+        // Characters without a colour can't get darker
+        assert_eq!(
+            effect.apply(ColChar::SOLID, fade_step),
+            Some(ColChar::SOLID)
+        );
+    }
+
+    #[test]
+    fn the_view_hides_cells_by_age_unless_it_flashes() {
+        let mut board = Board::new(4, 4);
+        board.fill(Vec2D::new(1, 3), ORANGE);
+        let one_second = u64::from(crate::FPS);
+        board.set_frame(one_second);
+        board.fill(Vec2D::new(2, 3), ORANGE);
+
+        let mut view = StackView {
+            board: &board,
+            effect: StackEffect::Invisible,
+            frame: one_second + 1,
+            flash: false,
+            scale: 1,
+        };
+        let shown = |view: &StackView| view.cells().map(|(pos, _)| pos.x).collect::<Vec<_>>();
+        assert_eq!(shown(&view), [2]);
+
+        view.flash = true;
+        assert_eq!(shown(&view), [1, 2]);
+    }
+}
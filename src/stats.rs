@@ -0,0 +1,37 @@
+use std::time::Duration;
+
+use crate::engine::FRAME_DURATION;
+
+/// Running totals kept over the course of a game
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct Stats {
+    pub pieces_placed: u32,
+    /// The number of frames the game has been running for
+    pub frames: u64,
+    /// The number of Singles, Doubles, Triples and Tetrises, in that order
+    pub line_clears: [u32; 4],
+    /// The number of times the board was cleared completely
+    pub perfect_clears: u32,
+    /// The number of garbage lines sent to opponents, after cancelling incoming garbage
+    pub lines_sent: u32,
+}
+
+impl Stats {
+    /// The time the game has been running for
+    #[must_use]
+    pub fn time(&self) -> Duration {
+        FRAME_DURATION * self.frames as u32
+    }
+}
+
+/// Format a duration as `minutes:seconds.hundredths`
+#[must_use]
+pub fn format_time(time: Duration) -> String {
+    let hundredths = time.as_millis() / 10;
+    format!(
+        "{}:{:02}.{:02}",
+        hundredths / 6000,
+        hundredths / 100 % 60,
+        hundredths % 100
+    )
+}